@@ -0,0 +1,96 @@
+use std::{fs::read_dir, time::Duration};
+
+use aarty::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use image::{imageops::FilterType, DynamicImage};
+
+/// Make a "next frame" by brightening a small square in the middle of the image,
+/// like a dashboard thumbnail that only changes in a small area.
+fn next_frame(image: &DynamicImage) -> DynamicImage {
+    let mut next = image.to_rgba8();
+    let (w, h) = next.dimensions();
+    for y in h / 3..h / 2 {
+        for x in w / 3..w / 2 {
+            let p = next.get_pixel_mut(x, y);
+            p.0[0] = p.0[0].saturating_add(90);
+            p.0[1] = p.0[1].saturating_add(90);
+            p.0[2] = p.0[2].saturating_add(90);
+        }
+    }
+    next.into()
+}
+
+fn frame_diff_bench(c: &mut Criterion) {
+    let mut images = Vec::new();
+
+    for entry in read_dir("samples").unwrap() {
+        let path = entry.unwrap().path();
+        let image = image::open(&path).unwrap();
+        let (w, h) = image.dimensions();
+        let image = image.resize(w / 4, h / 4, FilterType::Nearest);
+        let next = next_frame(&image);
+        images.push((path, image, next));
+    }
+
+    macro_rules! group {
+        ($name: expr, $config: expr) => {{
+            let mut group = c.benchmark_group($name);
+            for (path, image, next) in &images {
+                let prev = image.to_text($config);
+                let next = next.to_text($config);
+
+                let full = next.to_string().len();
+                let mut buf = Vec::new();
+                next.diff(&prev, &mut buf).unwrap();
+                println!(
+                    "{}/{}: full redraw {full} bytes, incremental redraw {} bytes",
+                    $name,
+                    path.display(),
+                    buf.len()
+                );
+
+                group.bench_with_input(
+                    BenchmarkId::new("full", path.to_str().unwrap()),
+                    &next,
+                    |b, next| {
+                        b.iter(|| {
+                            let mut out = Vec::with_capacity(full);
+                            std::io::Write::write_fmt(&mut out, format_args!("{next}")).unwrap();
+                            out
+                        });
+                    },
+                );
+                group.bench_with_input(
+                    BenchmarkId::new("incremental", path.to_str().unwrap()),
+                    &next,
+                    |b, next| {
+                        b.iter(|| {
+                            let mut out = Vec::with_capacity(buf.len());
+                            next.diff(&prev, &mut out).unwrap();
+                            out
+                        });
+                    },
+                );
+            }
+            group.finish();
+        }};
+    }
+
+    group!(
+        "redraw with no colors",
+        Config::new(vec![' ', '.', ',', '-', '~', '!', '*', '%', '$', '@', '#'].into())
+    );
+
+    group!(
+        "redraw with foeground colors",
+        Config::new(vec![' ', '.', ',', '-', '~', '!', '*', '%', '$', '@', '#'].into())
+            .with_flags(COLORS)
+    );
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10).with_plots().warm_up_time(Duration::from_millis(200));
+    targets = frame_diff_bench
+}
+criterion_main!(benches);
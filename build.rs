@@ -0,0 +1,38 @@
+//! Generate the shell completions and the man page of the binary.
+//!
+//! They're written into `$OUT_DIR`.
+
+fn main() {
+    #[cfg(feature = "cli")]
+    if let Err(e) = cli::generate() {
+        println!("cargo:warning=Can't generate the shell completions and the man page: {e}");
+    }
+}
+
+#[cfg(feature = "cli")]
+mod cli {
+    use std::{env, fs, io, path::PathBuf};
+
+    use clap::CommandFactory;
+    use clap_complete::{generate_to, Shell};
+    use clap_mangen::Man;
+
+    include!("src/bin/cli.rs");
+
+    pub(super) fn generate() -> io::Result<()> {
+        println!("cargo:rerun-if-changed=src/bin/cli.rs");
+
+        let out = PathBuf::from(env::var_os("OUT_DIR").expect("cargo sets OUT_DIR"));
+        let completions = out.join("completions");
+        fs::create_dir_all(&completions)?;
+
+        let mut cmd = Cli::command();
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            generate_to(shell, &mut cmd, "aarty", &completions)?;
+        }
+
+        let mut man = Vec::new();
+        Man::new(cmd).render(&mut man)?;
+        fs::write(out.join("aarty.1"), man)
+    }
+}
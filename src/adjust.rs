@@ -0,0 +1,159 @@
+use std::hash::{Hash, Hasher};
+
+use crate::Rgba;
+
+/// Tone and color adjustments, applied to every pixel before it's mapped to a symbol (and a color).
+///
+/// They're applied in this order: brightness, contrast, saturation, gamma, then invert. The alpha is never changed.
+///
+/// ```
+/// # use aarty::{adjust::Adjustments, fragments, Config, FragmentItem, PixelImage, Rgba, Sympols};
+/// struct Dark;
+///
+/// impl PixelImage for Dark {
+///     fn dimensions(&self) -> (u32, u32) {
+///         (4, 1)
+///     }
+///
+///     fn get_pixel(&self, x: u32, _: u32) -> Rgba {
+///         let v = x as u8 * 20;
+///         Rgba { r: v, g: v, b: v, a: 255 }
+///     }
+/// }
+///
+/// let symbols = |config: &Config| -> String {
+///     fragments(config, &Dark).filter_map(FragmentItem::fragment).map(|info| info.sym).collect()
+/// };
+///
+/// let config = Config::new(Sympols::new(vec![' ', '.', ':', '#']));
+/// assert_eq!(symbols(&config), "    ");
+///
+/// // Stretch the dark tones over the whole symbols set.
+/// let adjustments = Adjustments::default().with_brightness(0.4).with_contrast(4.);
+/// assert_eq!(symbols(&config.with_adjustments(adjustments)), " .:#");
+/// ```
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Adjustments {
+    /// Added to every channel, from -1 (black) to 1 (white), 0 keeps the image as it is.
+    pub brightness: f32,
+    /// Stretch the channels away from the mid gray, 1 keeps the image as it is and 0 makes it all gray.
+    pub contrast: f32,
+    /// Above 1 brightens the dark tones, below 1 darkens them.
+    pub gamma: f32,
+    /// 0 is grayscale, 1 keeps the colors as they are, and above 1 makes them more vivid.
+    pub saturation: f32,
+    /// Invert the colors (the negative).
+    pub invert: bool,
+}
+
+impl Adjustments {
+    /// The adjustments that keep the image as it is.
+    pub const NONE: Self = Self {
+        brightness: 0.,
+        contrast: 1.,
+        gamma: 1.,
+        saturation: 1.,
+        invert: false,
+    };
+
+    /// Set the brightness.
+    #[inline]
+    #[must_use]
+    pub fn with_brightness(mut self, brightness: f32) -> Self {
+        self.brightness = brightness;
+        self
+    }
+
+    /// Set the contrast.
+    #[inline]
+    #[must_use]
+    pub fn with_contrast(mut self, contrast: f32) -> Self {
+        self.contrast = contrast;
+        self
+    }
+
+    /// Set the gamma.
+    #[inline]
+    #[must_use]
+    pub fn with_gamma(mut self, gamma: f32) -> Self {
+        self.gamma = gamma;
+        self
+    }
+
+    /// Set the saturation.
+    #[inline]
+    #[must_use]
+    pub fn with_saturation(mut self, saturation: f32) -> Self {
+        self.saturation = saturation;
+        self
+    }
+
+    /// Set the invert mode.
+    #[inline]
+    #[must_use]
+    pub fn with_invert(mut self, invert: bool) -> Self {
+        self.invert = invert;
+        self
+    }
+
+    /// Return true if the adjustments keep the image as it is.
+    #[inline]
+    pub fn is_none(&self) -> bool {
+        *self == Self::NONE
+    }
+
+    /// Adjust a pixel.
+    pub fn apply(&self, pixel: Rgba) -> Rgba {
+        if self.is_none() {
+            return pixel;
+        }
+
+        let mut rgb = [pixel.r, pixel.g, pixel.b].map(|v| v as f32 / 255.);
+        for v in &mut rgb {
+            *v = ((*v + self.brightness - 0.5) * self.contrast + 0.5).clamp(0., 1.);
+        }
+        if self.saturation != 1. {
+            let [r, g, b] = rgb;
+            let luma = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+            for v in &mut rgb {
+                *v = (luma + (*v - luma) * self.saturation).clamp(0., 1.);
+            }
+        }
+        if self.gamma != 1. && self.gamma > 0. {
+            for v in &mut rgb {
+                *v = v.powf(1. / self.gamma);
+            }
+        }
+        if self.invert {
+            for v in &mut rgb {
+                *v = 1. - *v;
+            }
+        }
+
+        let [r, g, b] = rgb.map(|v| (v * 255.).round() as u8);
+        Rgba {
+            r,
+            g,
+            b,
+            a: pixel.a,
+        }
+    }
+}
+
+impl Default for Adjustments {
+    #[inline]
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+impl Hash for Adjustments {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for v in [self.brightness, self.contrast, self.gamma, self.saturation] {
+            v.to_bits().hash(state);
+        }
+        self.invert.hash(state);
+    }
+}
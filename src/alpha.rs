@@ -0,0 +1,155 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::Rgba;
+
+/// The checkerboard squares size, in pixels (the cells are about twice taller than their width).
+const CHECKER_SIZE: (u32, u32) = (4, 2);
+/// The checkerboard squares colors.
+const CHECKER_COLORS: [u8; 2] = [0x66, 0x99];
+
+/// How the transparent pixels are drawn.
+///
+/// Every pixel is composited before it's converted, so its symbol and its color always agree.
+///
+/// ```
+/// # #[cfg(all(feature = "image", feature = "colors"))] {
+/// # use aarty::{alpha::Alpha, fragments, Config, FragmentItem, Sympols, ANSIColor, COLORS};
+/// let image = image::open("samples/ok_hand.png").unwrap();
+/// let config = Config::new(Sympols::new(" .:-=+*#%@".chars().collect())).with_flags(COLORS);
+///
+/// // Without a background, the transparent pixels are left to the terminal default background
+/// // (they're the first symbol, without a color), and the rest are opaque.
+/// let infos: Vec<_> = fragments(&config, &image).filter_map(FragmentItem::fragment).collect();
+/// assert!(infos[0].fg.is_transparent());
+/// assert!(infos.iter().all(|info| !info.fg.is_transparent() || info.sym_index == 0));
+///
+/// // With a background, every pixel is blended over it.
+/// let config = config.with_background(ANSIColor::new(255, 255, 255));
+/// let infos: Vec<_> = fragments(&config, &image).filter_map(FragmentItem::fragment).collect();
+/// assert_eq!(infos[0].fg, ANSIColor::new(255, 255, 255));
+/// assert_eq!(infos[0].sym, '@');
+/// assert!(infos.iter().all(|info| !info.fg.is_transparent()));
+///
+/// // The half transparent edges are between the hand and the background, not random symbols.
+/// let black = config.clone().with_background(ANSIColor::new(0, 0, 0));
+/// let blended = fragments(&black, &image).filter_map(FragmentItem::fragment);
+/// let ignore = black.clone().with_alpha(Alpha::Ignore);
+/// let opaque = fragments(&ignore, &image).filter_map(FragmentItem::fragment);
+/// assert!(blended.zip(opaque).all(|(b, o)| b.sym_index <= o.sym_index));
+/// # }
+/// ```
+///
+/// An opaque image is the same with any mode:
+///
+/// ```
+/// # #[cfg(all(feature = "image", feature = "colors", feature = "text_image"))] {
+/// # use aarty::{alpha::Alpha, fragments, Config, Sympols, ToTextImage, ANSIColor, COLORS};
+/// let image = image::open("samples/archy.png").unwrap().thumbnail(80, 80);
+/// let config = Config::new(Sympols::new(" .:-=+*#%@".chars().collect())).with_flags(COLORS);
+/// let text = image.to_text(config.clone()).to_string();
+///
+/// assert_eq!(image.to_text(config.clone().with_alpha(Alpha::Ignore)).to_string(), text);
+/// assert_eq!(image.to_text(config.clone().with_alpha(Alpha::Checkerboard)).to_string(), text);
+///
+/// let with_background = config.clone().with_background(ANSIColor::new(9, 9, 9));
+/// assert!(fragments(&with_background, &image).eq(fragments(&config, &image)));
+/// # }
+/// ```
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Alpha {
+    /// Blend the pixels over the config background.
+    ///
+    /// If there's no background, the pixels that are less than half opaque are left to the terminal default background,
+    /// and the rest are drawn opaque.
+    #[default]
+    Blend,
+    /// Blend the pixels over a gray checkerboard, like the images editors do.
+    Checkerboard,
+    /// Ignore the alpha channel, every pixel is opaque.
+    Ignore,
+}
+
+impl Alpha {
+    /// Composite the pixel at `x` and `y` over the `background` (the terminal default background if it's [`None`]).
+    ///
+    /// The result is either opaque, or fully transparent (only without a background).
+    ///
+    /// ```
+    /// # use aarty::{alpha::Alpha, Rgba};
+    /// let red = Rgba { r: 255, g: 0, b: 0, a: 51 };
+    /// assert_eq!(
+    ///     Alpha::Blend.composite(red.clone(), 0, 0, Some((0, 0, 255))),
+    ///     Rgba { r: 51, g: 0, b: 204, a: 255 }
+    /// );
+    /// assert_eq!(Alpha::Blend.composite(red.clone(), 0, 0, None).a, 0);
+    /// assert_eq!(Alpha::Ignore.composite(red, 0, 0, None), Rgba { r: 255, g: 0, b: 0, a: 255 });
+    /// ```
+    pub fn composite(self, pixel: Rgba, x: u32, y: u32, background: Option<(u8, u8, u8)>) -> Rgba {
+        if pixel.a == u8::MAX || self == Alpha::Ignore {
+            return Rgba {
+                a: u8::MAX,
+                ..pixel
+            };
+        }
+        let background = match self {
+            Alpha::Checkerboard => {
+                let square = (x / CHECKER_SIZE.0 + y / CHECKER_SIZE.1) % 2;
+                let v = CHECKER_COLORS[square as usize];
+                (v, v, v)
+            }
+            _ => match background {
+                Some(background) => background,
+                None if pixel.a < 128 => {
+                    return Rgba {
+                        r: 0,
+                        g: 0,
+                        b: 0,
+                        a: 0,
+                    }
+                }
+                None => {
+                    return Rgba {
+                        a: u8::MAX,
+                        ..pixel
+                    }
+                }
+            },
+        };
+
+        let a = pixel.a as u16;
+        let blend = |v: u8, bg: u8| ((v as u16 * a + bg as u16 * (255 - a) + 127) / 255) as u8;
+        Rgba {
+            r: blend(pixel.r, background.0),
+            g: blend(pixel.g, background.1),
+            b: blend(pixel.b, background.2),
+            a: u8::MAX,
+        }
+    }
+}
+
+impl FromStr for Alpha {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "blend" => Ok(Alpha::Blend),
+            "checkerboard" | "checker" => Ok(Alpha::Checkerboard),
+            "ignore" | "opaque" => Ok(Alpha::Ignore),
+            other => Err(format!(
+                "unknown alpha mode `{other}`, expected one of blend, checkerboard or ignore"
+            )),
+        }
+    }
+}
+
+impl Display for Alpha {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Alpha::Blend => "blend",
+            Alpha::Checkerboard => "checkerboard",
+            Alpha::Ignore => "ignore",
+        })
+    }
+}
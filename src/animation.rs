@@ -0,0 +1,345 @@
+use std::{
+    borrow::Borrow,
+    error::Error,
+    io::Write,
+    num::NonZeroU32,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::TextImage;
+
+/// Move the cursor to the top left corner of the terminal.
+pub(crate) const CURSOR_HOME: &str = "\u{001b}[H";
+/// Clear the whole screen.
+const CLEAR_SCREEN: &str = "\u{001b}[2J";
+/// The delays up to this are too short to be real (e.g. the GIFs without a delay), like what the browsers do.
+const MIN_DELAY: Duration = Duration::from_millis(10);
+/// The delay that replaces the too short ones.
+const DEFAULT_DELAY: Duration = Duration::from_millis(100);
+
+/// The delay that the frame is shown for, the too short delays are replaced by 100ms.
+#[inline]
+pub(crate) fn frame_delay(delay: Duration) -> Duration {
+    if delay <= MIN_DELAY {
+        DEFAULT_DELAY
+    } else {
+        delay
+    }
+}
+
+/// A single animation frame.
+#[derive(Debug, PartialEq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnimationFrame {
+    /// The rendered frame.
+    pub image: TextImage,
+    /// How long the frame should stay on the screen.
+    pub delay: Duration,
+}
+
+impl AnimationFrame {
+    /// Construct a new instance.
+    #[inline]
+    #[must_use]
+    pub const fn new(image: TextImage, delay: Duration) -> Self {
+        Self { image, delay }
+    }
+}
+
+/// How many times an [`Animation`] should be played.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Repeat {
+    /// Loop forever.
+    #[default]
+    Infinite,
+    /// Play the animation n times.
+    Times(NonZeroU32),
+}
+
+impl From<u32> for Repeat {
+    /// `0` means [`Repeat::Infinite`].
+    fn from(n: u32) -> Self {
+        match NonZeroU32::new(n) {
+            Some(n) => Repeat::Times(n),
+            None => Repeat::Infinite,
+        }
+    }
+}
+
+/// Draws [`TextImage`]s on top of each others from the cursor home position.
+///
+/// The first frame gets fully drawn, and the successive frames are drawn with [`TextImage::diff`] when its possible
+/// (a.k.a. when they have the same size as the previous frame), so only the changed fragments are sent to the terminal.
+/// A frame with another size clears the screen first.
+#[derive(Debug)]
+pub struct Player<W> {
+    out: W,
+    prev: Option<TextImage>,
+    deadline: Option<Instant>,
+}
+
+impl<W: Write> Player<W> {
+    /// Construct a new instance.
+    #[inline]
+    #[must_use]
+    pub const fn new(out: W) -> Self {
+        Self {
+            out,
+            prev: None,
+            deadline: None,
+        }
+    }
+
+    /// Draw a frame and flush the output.
+    pub fn draw(&mut self, frame: &TextImage) -> Result<(), Box<dyn Error>> {
+        self.out.write_all(CURSOR_HOME.as_bytes())?;
+        match &self.prev {
+            Some(prev) if prev.row_len == frame.row_len && prev.len() == frame.len() => {
+                frame.diff(prev, &mut self.out)?
+            }
+            // The previous frame had another size, so its cells might stick out of this one.
+            Some(_) => write!(self.out, "{CLEAR_SCREEN}{CURSOR_HOME}{frame}")?,
+            None => write!(self.out, "{frame}")?,
+        }
+        self.out.flush()?;
+        self.prev = Some(frame.clone());
+        Ok(())
+    }
+
+    /// Draw the frames one after another, waiting for each frame delay (a delay up to 10ms is played as 100ms).
+    ///
+    /// The frames can be lazily produced (e.g. decoded from a video stream), the time that takes
+    /// to produce a frame is subtracted from the previous frame delay.
+    pub fn play<I>(&mut self, frames: I) -> Result<(), Box<dyn Error>>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<AnimationFrame>,
+    {
+        for frame in frames {
+            let frame = frame.borrow();
+            self.wait();
+            self.draw(&frame.image)?;
+            self.deadline = Some(Instant::now() + frame_delay(frame.delay));
+        }
+        Ok(())
+    }
+
+    /// Wait until the previous frame delay is over.
+    #[inline]
+    fn wait(&mut self) {
+        if let Some(deadline) = self.deadline.take() {
+            thread::sleep(deadline.saturating_duration_since(Instant::now()));
+        }
+    }
+
+    /// Wait for the last frame delay, then move the cursor to the line after it.
+    pub fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        self.wait();
+        if let Some(prev) = &self.prev {
+            write!(self.out, "\u{001b}[{};1H", prev.rows() + 1)?;
+            self.out.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Forget about the previous frame, so the next frame will be fully redrawn.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.prev = None;
+        self.deadline = None;
+    }
+
+    /// Get a mutable reference to the inner writer.
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.out
+    }
+
+    /// Return the inner writer.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// A sequence of rendered frames.
+#[derive(Debug, PartialEq, Clone, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Animation {
+    frames: Vec<AnimationFrame>,
+}
+
+impl Animation {
+    /// Construct a new instance.
+    #[inline]
+    #[must_use]
+    pub const fn new(frames: Vec<AnimationFrame>) -> Self {
+        Self { frames }
+    }
+
+    /// The animation frames.
+    #[inline(always)]
+    pub fn frames(&self) -> &[AnimationFrame] {
+        &self.frames
+    }
+
+    /// The frames number.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Return true if the animation has no frames.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The time that one loop of the animation takes.
+    pub fn duration(&self) -> Duration {
+        self.frames.iter().map(|f| f.delay).sum()
+    }
+
+    /// Play the animation in the terminal, an animation without frames returns right away,
+    /// and a single frame is drawn once (even if it loops forever).
+    ///
+    /// ```no_run
+    /// # use aarty::*;
+    /// # use aarty::animation::{Animation, Repeat};
+    /// # use std::{fs::File, io::{self, BufReader}};
+    /// let cfg = Config::new(vec![' ', '.', ',', '-', '~', '!', '*', '%', '$', '@', '#'].into()).with_flags(COLORS);
+    ///
+    /// let reader = BufReader::new(File::open("cat.gif").unwrap());
+    /// let frames = aarty::animation::decode_frames(reader, image::ImageFormat::Gif).unwrap();
+    /// let animation = Animation::from_frames(frames, &cfg, |frame| frame.thumbnail(80, 80)).unwrap();
+    ///
+    /// animation.play(io::stdout().lock(), Repeat::Infinite).expect("IO error");
+    /// ```
+    ///
+    /// ```
+    /// # use aarty::animation::{Animation, Repeat};
+    /// let mut out = Vec::new();
+    /// Animation::default().play(&mut out, Repeat::Infinite).unwrap();
+    /// assert!(out.is_empty());
+    ///
+    /// # use aarty::{animation::AnimationFrame, Config, IndexdFragment, Sympols, TextImage};
+    /// # use std::time::Duration;
+    /// let mut frame = TextImage::new(Config::new(Sympols::new(vec!['#'])), 1, 1);
+    /// frame.insert(0, IndexdFragment::new(0));
+    /// let still = Animation::new(vec![AnimationFrame::new(frame, Duration::ZERO)]);
+    /// still.play(&mut out, Repeat::Infinite).unwrap();
+    /// assert_eq!(out.iter().filter(|b| **b == b'#').count(), 1);
+    /// ```
+    pub fn play<W>(&self, out: W, repeat: Repeat) -> Result<(), Box<dyn Error>>
+    where
+        W: Write,
+    {
+        if self.is_empty() {
+            return Ok(());
+        }
+        let mut player = Player::new(out);
+        // There's nothing to animate, redrawing it forever only burns the CPU.
+        let repeat = if self.len() == 1 {
+            Repeat::Times(NonZeroU32::MIN)
+        } else {
+            repeat
+        };
+        match repeat {
+            Repeat::Infinite => loop {
+                player.play(&self.frames)?;
+            },
+            Repeat::Times(n) => {
+                for _ in 0..n.get() {
+                    player.play(&self.frames)?;
+                }
+            }
+        }
+        player.finish()
+    }
+}
+
+impl From<Vec<AnimationFrame>> for Animation {
+    #[inline]
+    fn from(frames: Vec<AnimationFrame>) -> Self {
+        Self::new(frames)
+    }
+}
+
+impl FromIterator<AnimationFrame> for Animation {
+    fn from_iter<T: IntoIterator<Item = AnimationFrame>>(iter: T) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+#[cfg(feature = "image")]
+pub use self::decode::decode_frames;
+
+#[cfg(feature = "image")]
+mod decode {
+    use std::io::{BufRead, Seek};
+
+    use image::{
+        codecs::{gif::GifDecoder, png::PngDecoder, webp::WebPDecoder},
+        AnimationDecoder, DynamicImage, Frame, Frames, ImageFormat, ImageResult,
+    };
+
+    use super::{Animation, AnimationFrame};
+    use crate::{Config, ToTextImage};
+
+    /// Decode the frames of an animated GIF, APNG or WebP image.
+    ///
+    /// The other formats (and the not animated PNG and WebP images) are decoded as a single frame.
+    pub fn decode_frames<'a, R>(reader: R, format: ImageFormat) -> ImageResult<Frames<'a>>
+    where
+        R: BufRead + Seek + 'a,
+    {
+        match format {
+            ImageFormat::Gif => Ok(GifDecoder::new(reader)?.into_frames()),
+            ImageFormat::Png => {
+                let decoder = PngDecoder::new(reader)?;
+                if decoder.is_apng()? {
+                    Ok(decoder.apng()?.into_frames())
+                } else {
+                    single_frame(DynamicImage::from_decoder(decoder)?)
+                }
+            }
+            ImageFormat::WebP => {
+                let decoder = WebPDecoder::new(reader)?;
+                if decoder.has_animation() {
+                    Ok(decoder.into_frames())
+                } else {
+                    single_frame(DynamicImage::from_decoder(decoder)?)
+                }
+            }
+            format => single_frame(image::load(reader, format)?),
+        }
+    }
+
+    #[inline]
+    fn single_frame<'a>(image: DynamicImage) -> ImageResult<Frames<'a>> {
+        Ok(Frames::new(Box::new(std::iter::once(Ok(Frame::new(
+            image.into_rgba8(),
+        ))))))
+    }
+
+    impl Animation {
+        /// Convert the decoded frames into an [`Animation`].
+        ///
+        /// `map` is called on every frame before the conversion, which is the place to resize them.
+        pub fn from_frames<F>(frames: Frames<'_>, cfg: &Config, mut map: F) -> ImageResult<Self>
+        where
+            F: FnMut(DynamicImage) -> DynamicImage,
+        {
+            frames
+                .map(|frame| {
+                    let frame = frame?;
+                    let delay = frame.delay().into();
+                    let image = map(frame.into_buffer().into()).to_text(cfg.clone());
+                    Ok(AnimationFrame::new(image, delay))
+                })
+                .collect()
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::{
+    borrow::Borrow,
+    error::Error,
+    io::{self, Write},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::animation::{AnimationFrame, Player};
+
+/// The terminal size in the header of a recording without frames.
+const EMPTY_SIZE: (u32, u32) = (80, 24);
+
+/// A writer that turns everything that gets written into it into an [asciicast v2](https://docs.asciinema.org/manual/asciicast/v2/) file.
+///
+/// The written bytes are buffered until the next flush, then they're recorded as one output (`o`) event
+/// at the current (virtual) time, which only moves forward with [`AsciicastWriter::advance`].
+///
+/// ```
+/// # use aarty::asciicast::AsciicastWriter;
+/// # use std::{io::Write, time::Duration};
+/// let mut cast = AsciicastWriter::new(Vec::new(), 80, 24).unwrap();
+/// write!(cast, "hello\n").unwrap();
+/// cast.flush().unwrap();
+/// cast.advance(Duration::from_millis(1500));
+/// write!(cast, "\x1b[Hworld").unwrap();
+///
+/// let cast = String::from_utf8(cast.into_inner().unwrap()).unwrap();
+/// let events: Vec<_> = cast.lines().skip(1).collect();
+/// assert_eq!(events, [r#"[0.000000, "o", "hello\r\n"]"#, r#"[1.500000, "o", "\u001b[Hworld"]"#]);
+/// ```
+#[derive(Debug)]
+pub struct AsciicastWriter<W: Write> {
+    out: W,
+    time: Duration,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> AsciicastWriter<W> {
+    /// Write the asciicast header and construct a new instance.
+    pub fn new(mut out: W, width: u32, height: u32) -> io::Result<Self> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |t| t.as_secs());
+        writeln!(
+            out,
+            r#"{{"version": 2, "width": {width}, "height": {height}, "timestamp": {timestamp}}}"#
+        )?;
+        Ok(Self {
+            out,
+            time: Duration::ZERO,
+            pending: Vec::new(),
+        })
+    }
+
+    /// The current time in the recording.
+    #[inline(always)]
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Move the recording time forward.
+    #[inline]
+    pub fn advance(&mut self, by: Duration) {
+        self.time += by;
+    }
+
+    /// Write an output event at the current time.
+    ///
+    /// The bare line feeds get translated to `\r\n`, like what the terminal driver does with the programs output.
+    pub fn event(&mut self, data: &[u8]) -> io::Result<()> {
+        let data = String::from_utf8_lossy(data);
+        write!(self.out, "[{:.6}, \"o\", \"", self.time.as_secs_f64())?;
+        let mut prev = None;
+        for ch in data.chars() {
+            match ch {
+                '"' => self.out.write_all(b"\\\"")?,
+                '\\' => self.out.write_all(b"\\\\")?,
+                '\n' if prev != Some('\r') => self.out.write_all(b"\\r\\n")?,
+                '\n' => self.out.write_all(b"\\n")?,
+                '\r' => self.out.write_all(b"\\r")?,
+                '\t' => self.out.write_all(b"\\t")?,
+                ch if ch.is_control() => write!(self.out, "\\u{:04x}", ch as u32)?,
+                ch => write!(self.out, "{ch}")?,
+            }
+            prev = Some(ch);
+        }
+        self.out.write_all(b"\"]\n")
+    }
+
+    /// Flush the pending output and return the inner writer.
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.flush()?;
+        Ok(self.out)
+    }
+}
+
+impl<W: Write> Write for AsciicastWriter<W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            let pending = std::mem::take(&mut self.pending);
+            self.event(&pending)?;
+        }
+        self.out.flush()
+    }
+}
+
+/// Records rendered frames into an asciicast v2 file, without waiting for the frames delays.
+///
+/// The terminal size in the header is taken from the first frame, its width is in display columns (See: [`crate::TextImage::width`]).
+/// A recording without frames still gets the header (with an 80x24 size), so the file is always valid.
+///
+/// ```
+/// # use aarty::asciicast::Recorder;
+/// let cast = Recorder::new(Vec::new()).finish().unwrap().unwrap();
+/// let cast = String::from_utf8(cast).unwrap();
+/// assert!(cast.starts_with(r#"{"version": 2, "width": 80, "height": 24"#));
+/// assert_eq!(cast.lines().count(), 1);
+/// ```
+///
+/// ```no_run
+/// # use aarty::*;
+/// # use aarty::{animation::Animation, asciicast::Recorder};
+/// # use std::{fs::File, io::BufReader};
+/// let cfg = Config::new(vec![' ', '.', ',', '-', '~', '!', '*', '%', '$', '@', '#'].into());
+///
+/// let reader = BufReader::new(File::open("cat.gif").unwrap());
+/// let frames = aarty::animation::decode_frames(reader, image::ImageFormat::Gif).unwrap();
+/// let animation = Animation::from_frames(frames, &cfg, |frame| frame.thumbnail(80, 80)).unwrap();
+///
+/// let mut recorder = Recorder::new(File::create("cat.cast").unwrap());
+/// recorder.record(animation.frames()).expect("IO error");
+/// recorder.finish().expect("IO error");
+/// ```
+#[derive(Debug)]
+pub struct Recorder<W: Write> {
+    out: Option<W>,
+    player: Option<Player<AsciicastWriter<W>>>,
+}
+
+impl<W: Write> Recorder<W> {
+    /// Construct a new instance.
+    #[inline]
+    #[must_use]
+    pub const fn new(out: W) -> Self {
+        Self {
+            out: Some(out),
+            player: None,
+        }
+    }
+
+    /// Record the frames, each frame gets its delay in the recording timeline (a delay up to 10ms is recorded as 100ms).
+    pub fn record<I>(&mut self, frames: I) -> Result<(), Box<dyn Error>>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<AnimationFrame>,
+    {
+        for frame in frames {
+            let frame = frame.borrow();
+            let player = match &mut self.player {
+                Some(player) => player,
+                None => {
+                    let out = self.out.take().ok_or("the recorder is broken")?;
+                    let (w, h) = (frame.image.width(), frame.image.rows() + 1);
+                    self.player
+                        .insert(Player::new(AsciicastWriter::new(out, w as u32, h as u32)?))
+                }
+            };
+            player.draw(&frame.image)?;
+            player
+                .get_mut()
+                .advance(crate::animation::frame_delay(frame.delay));
+        }
+        Ok(())
+    }
+
+    /// Finish the recording and return the inner writer.
+    ///
+    /// If no frames were recorded, only the header is written, and [`None`] is returned
+    /// if the recorder failed to write the header.
+    pub fn finish(self) -> Result<Option<W>, Box<dyn Error>> {
+        let Some(mut player) = self.player else {
+            let Some(out) = self.out else {
+                return Ok(None);
+            };
+            let (w, h) = EMPTY_SIZE;
+            return Ok(Some(AsciicastWriter::new(out, w, h)?.into_inner()?));
+        };
+        player.finish()?;
+        Ok(Some(player.into_inner().into_inner()?))
+    }
+}
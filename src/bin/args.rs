@@ -1,84 +1,318 @@
-use std::{env, num::NonZeroU8, process};
+use std::{num::NonZeroU8, path::PathBuf};
 
-use aarty::{COLORS, REVERSE};
-use image::imageops::FilterType;
+use aarty::{
+    adjust::Adjustments, alpha::Alpha, graphics::Graphics, levels::Levels, resample::Sampler,
+    sympols, terminal::Fit, ANSIColor, ColorDepth, Sympols, CELL_COLORS, COLORS, REVERSE,
+};
+use clap::{error::ErrorKind, CommandFactory, Parser};
 
-const VERSION: &str = env!("CARGO_PKG_VERSION");
+use crate::{
+    batch::OutputFormat,
+    cli::{Cli, Command},
+    config::{ConfigFile, Profile},
+};
+
+/// How to choose the terminal graphics protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphicsMode {
+    /// Use the best protocol that the terminal supports
+    Auto,
+    /// Always render the image as text
+    Off,
+    Use(Graphics),
+}
 
 pub struct Opts {
-    /// The image to convert to ASCII art
-    pub path: Option<String>,
-    /// The character to use for drawing the image (lighter to darker)
+    /// The images to convert to ASCII art
+    pub paths: Vec<String>,
+    /// The character to use for drawing the image (from the darker pixels to the lighter ones, e.g. " .:#")
     /// You can user one character if you uses the color mode
-    pub sym_set: Vec<char>,
-    /// The output scale (1 is the original size)
-    pub scale: NonZeroU8,
+    pub sym_set: Sympols,
+    /// The output scale (1 is the original size), the image fits the terminal when
+    /// there's no scale, width or height
+    pub scale: Option<NonZeroU8>,
     /// Enstablish how much wide is the output images, in columns. Overrides `scale`
     pub width: Option<u32>,
     pub height: Option<u32>,
-    /// The background color to use
-    pub background: Option<String>,
+    /// The background color to use (`#rgb`, `#rrggbb`, `rgb(...)`, `hsl(...)`, a color name or a palette index)
+    pub background: Option<ANSIColor>,
     pub flags: u8,
-    pub sf: FilterType,
+    /// Enable the colors if the terminal supports them, unless they're disabled explicitly
+    pub auto_colors: bool,
+    /// The scaling filter
+    pub sampler: Sampler,
+    /// How to fit the image into the terminal (or into the width and height if they're specified)
+    pub fit: Option<Fit>,
+    /// Override the detected color depth
+    pub depth: Option<ColorDepth>,
+    /// Display the image with a terminal graphics protocol instead of text
+    pub graphics: GraphicsMode,
+    /// Play all the frames of an animated image (GIF, APNG, WebP)
+    pub animate: bool,
+    /// How many times to play the animation, 0 means forever
+    pub loops: u32,
+    /// Read a YUV4MPEG2 stream
+    pub y4m: bool,
+    /// Read a raw `rgb24` stream with the given frame size
+    pub raw: Option<(u32, u32)>,
+    /// The stream frame rate, overrides the YUV4MPEG2 header
+    pub fps: Option<f64>,
+    /// Record the animation (or the stream) into an asciicast v2 file instead of playing it
+    pub record: Option<String>,
+    /// Write the converted images into this directory
+    pub output_dir: Option<PathBuf>,
+    /// The output files names template
+    pub name_template: String,
+    /// The converted images format
+    pub format: OutputFormat,
+    /// Tile the images in a grid
+    pub montage: bool,
+    /// The montage columns number
+    pub columns: usize,
+    /// The spaces between the montage columns, and the empty lines between its rows
+    pub gutter: usize,
+    /// Write the file names under the montage images
+    pub captions: bool,
+    /// Browse the images in the interactive viewer
+    pub view: bool,
+    /// Re-render the image whenever the file changes
+    pub watch: bool,
+    /// The crop rectangle (x, y, width and height)
+    pub crop: Option<(u32, u32, u32, u32)>,
+    /// The clockwise rotation, in degrees
+    pub rotate: u32,
+    /// Mirror the image horizontally
+    pub flip_h: bool,
+    /// Mirror the image vertically
+    pub flip_v: bool,
+    /// The brightness, contrast, gamma, saturation and invert adjustments
+    pub adjustments: Adjustments,
+    /// How the brightness is mapped to the characters
+    pub levels: Levels,
+    /// How the transparent pixels are drawn
+    pub alpha: Alpha,
 }
 
 impl Opts {
     pub fn from_args() -> Result<Opts, String> {
-        let mut args = env::args().skip(1);
-        let mut opts = Opts::default();
+        Cli::parse().try_into()
+    }
+
+    /// The first input path.
+    #[inline]
+    pub fn path(&self) -> Option<&String> {
+        self.paths.first()
+    }
+}
 
+impl TryFrom<Cli> for Opts {
+    type Error = String;
+
+    fn try_from(cli: Cli) -> Result<Self, Self::Error> {
         macro_rules! err {
-            ($arg:ident) => {
-                format!("Expected one argument after {}, found 0.", $arg)
-            };
-            (parse; $item:expr, $error:ident) => {
-                format!("Can't parse the provided {}, because `{}`", $item, $error)
+            (parse; $item:expr, $error:expr) => {
+                Cli::command()
+                    .error(
+                        ErrorKind::ValueValidation,
+                        format!("Can't parse the provided {}, because `{}`", $item, $error),
+                    )
+                    .to_string()
+                    .trim_end()
+                    .to_string()
             };
         }
 
-        macro_rules! value {
-            ($arg:ident) => {
-                args.next().ok_or(err!($arg))
-            };
-            (parse; $name:expr, $arg:ident) => {
-                args
-                    .next()
-                    .ok_or(err!($arg))?
-                    .parse()
-                    .map_err(|e| err!(parse; $name, e))
-            }
+        let file = ConfigFile::load(cli.config.as_deref())?;
+        let profile = file.profile(cli.profile.as_deref())?;
+        let profile = Profile {
+            chars: cli.sym_set,
+            preset: cli.preset,
+            scale: cli.scale,
+            width: cli.width,
+            height: cli.height,
+            fit: cli.fit,
+            filter: cli.filter,
+            colors: switch(cli.colors, cli.no_colors),
+            reverse: switch(cli.reverse, cli.no_reverse),
+            cell_colors: switch(cli.cell_colors, cli.no_cell_colors),
+            background: cli.background,
+            depth: cli.depth,
+            graphics: cli.graphics,
+            format: cli.format,
+            loops: cli.loops,
+            fps: cli.fps,
+            brightness: cli.brightness,
+            contrast: cli.contrast,
+            gamma: cli.gamma,
+            saturation: cli.saturation,
+            invert: switch(cli.invert, cli.no_invert),
+            levels: cli.levels,
+            alpha: cli.alpha,
         }
+        .or(profile);
 
-        while let Some(arg) = args.next() {
-            if !arg.starts_with('-') {
-                opts.path = Some(arg);
-                continue;
-            }
-            let arg = arg.to_lowercase();
-            let arg = arg.trim_start_matches('-');
-            match arg {
-                "c" | "sympols" | "chars" => opts.sym_set = value!(arg)?.chars().collect(),
-                "s" | "scale" => {
-                    opts.scale = NonZeroU8::new(value!(parse; "scale", arg)?)
-                        .ok_or_else(|| "The scale shouldn't be less than 1,".to_string())?
-                }
-                "w" | "col" | "columans" | "width" => {
-                    opts.width = Some(value!(parse; "width", arg)?)
-                }
-                "h" | "row" | "rows" | "height" => {
-                    opts.height = Some(value!(parse; "height", arg)?)
+        if cli.watch && cli.paths.len() != 1 {
+            return Err(err!(parse; "paths", "the watch mode needs a single file"));
+        }
+        if cli.paths.len() > 1 && (cli.animate || cli.y4m || cli.raw.is_some()) {
+            return Err(
+                err!(parse; "paths", "the animations and the streams accept a single input"),
+            );
+        }
+
+        let (paths, view) = match cli.command {
+            Some(Command::View { paths }) => (paths, true),
+            None => (cli.paths, false),
+        };
+
+        let mut opts = Opts {
+            paths,
+            view,
+            watch: cli.watch,
+            output_dir: cli.output_dir.map(PathBuf::from),
+            name_template: cli.name_template,
+            montage: cli.montage,
+            captions: cli.captions,
+            scale: profile.scale,
+            width: profile.width,
+            height: profile.height,
+            y4m: cli.y4m,
+            record: cli.record,
+            animate: cli.animate,
+            flip_h: cli.flip_h,
+            flip_v: cli.flip_v,
+            ..Default::default()
+        };
+
+        if let Some(sym_set) = profile.chars {
+            opts.sym_set = sym_set.chars().collect::<Vec<_>>().into();
+        } else if let Some(preset) = profile.preset {
+            opts.sym_set = Sympols::preset(&preset).ok_or_else(|| err!(parse; "preset", preset))?;
+        }
+        if let Some(background) = profile.background {
+            opts.background = Some(
+                background
+                    .parse()
+                    .map_err(|e| err!(parse; "background color", e))?,
+            );
+        }
+        match profile.colors {
+            Some(true) => opts.flags |= COLORS,
+            Some(false) => opts.auto_colors = false,
+            None => {}
+        }
+        if profile.reverse == Some(true) {
+            opts.flags |= REVERSE;
+        }
+        if profile.cell_colors == Some(true) && profile.colors != Some(false) {
+            opts.flags |= CELL_COLORS | COLORS;
+        }
+        if let Some(filter) = profile.filter {
+            opts.sampler = filter
+                .parse()
+                .map_err(|e| err!(parse; "scaling filter", e))?;
+        }
+        opts.fit = profile
+            .fit
+            .map(|fit| fit.parse().map_err(|e| err!(parse; "fit mode", e)))
+            .transpose()?;
+        opts.depth = profile
+            .depth
+            .map(|depth| match depth.as_str() {
+                "truecolor" | "24bit" => Ok(ColorDepth::TrueColor),
+                "256" => Ok(ColorDepth::Ansi256),
+                "16" => Ok(ColorDepth::Ansi16),
+                other => Err(err!(parse; "color depth", other)),
+            })
+            .transpose()?;
+        if let Some(graphics) = profile.graphics {
+            opts.graphics = match graphics.as_str() {
+                "auto" => GraphicsMode::Auto,
+                "none" | "off" => GraphicsMode::Off,
+                other => GraphicsMode::Use(
+                    other
+                        .parse()
+                        .map_err(|e| err!(parse; "graphics protocol", e))?,
+                ),
+            };
+        }
+        if let Some(format) = profile.format {
+            opts.format = format
+                .parse()
+                .map_err(|e| err!(parse; "output format", e))?;
+        }
+        if let Some(columns) = cli.columns {
+            opts.columns = columns.get() as usize;
+        }
+        if let Some(gutter) = cli.gutter {
+            opts.gutter = gutter as usize;
+        }
+        if let Some(loops) = profile.loops {
+            opts.loops = loops;
+        }
+        if let Some(size) = cli.raw {
+            let (w, h) = size
+                .split_once('x')
+                .ok_or_else(|| err!(parse; "raw frame size", "expected WxH"))?;
+            let parse = |v: &str| match v.parse::<u32>() {
+                Ok(0) => Err(err!(parse; "raw frame size", "the frame can't be empty")),
+                Ok(v) => Ok(v),
+                Err(e) => Err(err!(parse; "raw frame size", e)),
+            };
+            opts.raw = Some((parse(w)?, parse(h)?));
+        }
+        let adjustment =
+            |name: &str, value: Option<f32>, valid: fn(f32) -> bool, default: f32| match value {
+                Some(v) if !v.is_finite() || !valid(v) => {
+                    Err(err!(parse; name, format!("{v} is out of range")))
                 }
-                "b" | "back" | "background" => opts.background = Some(value!(arg)?),
-                "r" | "reverse" => opts.flags |= REVERSE,
-                "u" | "color" | "colors" => opts.flags |= COLORS,
-                "sft" | "st" => opts.sf = FilterType::Triangle,
-                "sfc" | "sc" => opts.sf = FilterType::CatmullRom,
-                "sfg" | "sg" => opts.sf = FilterType::Gaussian,
-                "sfl" | "sl" => opts.sf = FilterType::Lanczos3,
-                "sfn" | "sn" => opts.sf = FilterType::Nearest,
-                "v" | "version" => info(format!("aarty v{VERSION}")),
-                unknown => return Err(format!("Unknown option {unknown}")),
+                v => Ok(v.unwrap_or(default)),
+            };
+        opts.adjustments = Adjustments::default()
+            .with_brightness(adjustment(
+                "brightness",
+                profile.brightness,
+                |v| (-1. ..=1.).contains(&v),
+                0.,
+            )?)
+            .with_contrast(adjustment("contrast", profile.contrast, |v| v >= 0., 1.)?)
+            .with_gamma(adjustment("gamma", profile.gamma, |v| v > 0., 1.)?)
+            .with_saturation(adjustment(
+                "saturation",
+                profile.saturation,
+                |v| v >= 0.,
+                1.,
+            )?)
+            .with_invert(profile.invert == Some(true));
+        if let Some(alpha) = profile.alpha {
+            opts.alpha = alpha.parse().map_err(|e| err!(parse; "alpha mode", e))?;
+        }
+        if let Some(levels) = profile.levels {
+            opts.levels = levels.parse().map_err(|e| err!(parse; "levels mode", e))?;
+        }
+        if let Some(crop) = cli.crop {
+            let parse = |v: &str| v.parse().map_err(|e| err!(parse; "crop rectangle", e));
+            let (size, offset) = crop.split_once('+').unwrap_or((&crop, "0+0"));
+            let (w, h) = size
+                .split_once('x')
+                .ok_or_else(|| err!(parse; "crop rectangle", "expected WxH[+X+Y]"))?;
+            let (x, y) = offset
+                .split_once('+')
+                .ok_or_else(|| err!(parse; "crop rectangle", "expected WxH[+X+Y]"))?;
+            let (w, h) = (parse(w)?, parse(h)?);
+            if w == 0 || h == 0 {
+                return Err(err!(parse; "crop rectangle", "the rectangle is empty"));
             }
+            opts.crop = Some((parse(x)?, parse(y)?, w, h));
+        }
+        if let Some(rotate) = cli.rotate {
+            opts.rotate = rotate.parse().map_err(|e| err!(parse; "rotation", e))?;
+        }
+        if let Some(fps) = profile.fps {
+            if !fps.is_normal() || fps < 0. {
+                return Err(err!(parse; "fps", "The fps should be a positive number"));
+            }
+            opts.fps = Some(fps);
         }
 
         Ok(opts)
@@ -88,22 +322,50 @@ impl Opts {
 impl Default for Opts {
     fn default() -> Self {
         Opts {
-            path: None,
-            sym_set: vec![
-                ' ', '.', ',', '-', '~', '!', ';', ':', '=', '*', '&', '%', '$', '@', '#',
-            ],
-            scale: unsafe { NonZeroU8::new_unchecked(4) },
+            paths: Vec::new(),
+            sym_set: sympols::DEFAULT.into(),
+            scale: None,
             width: None,
             height: None,
             background: None,
             flags: 0,
-            sf: FilterType::Nearest,
+            auto_colors: true,
+            sampler: Sampler::Nearest,
+            fit: None,
+            depth: None,
+            graphics: GraphicsMode::Off,
+            animate: false,
+            loops: 0,
+            y4m: false,
+            raw: None,
+            fps: None,
+            record: None,
+            output_dir: None,
+            name_template: "{path}.{ext}".to_string(),
+            format: OutputFormat::default(),
+            montage: false,
+            columns: 4,
+            gutter: 2,
+            captions: false,
+            view: false,
+            watch: false,
+            crop: None,
+            rotate: 0,
+            flip_h: false,
+            flip_v: false,
+            adjustments: Adjustments::NONE,
+            levels: Levels::Absolute,
+            alpha: Alpha::Blend,
         }
     }
 }
 
-#[cold]
-fn info(msg: String) -> ! {
-    println!("{msg}");
-    process::exit(0)
+/// The value of an on and off flags pair, [`None`] if neither of them is used.
+#[inline]
+fn switch(on: bool, off: bool) -> Option<bool> {
+    match (on, off) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    }
 }
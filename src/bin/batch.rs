@@ -0,0 +1,482 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Write as _,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use aarty::{
+    convert_image_to_ascii,
+    montage::Montage,
+    terminal::{terminal_size, Fit, CELL_ASPECT},
+    ANSIColor, ColorDepth, Config, FragmentInfo, FragmentWriter, ToTextImage, COLORS,
+};
+use image::{DynamicImage, GenericImageView, ImageError, ImageFormat};
+use rayon::prelude::*;
+
+use crate::{args::Opts, fit_size, resample, resize, transform};
+
+/// The format of the converted files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// The text with the ANSI escape codes, as it's displayed in the terminal
+    #[default]
+    Ansi,
+    /// Plain text, without colors
+    Text,
+    /// A HTML page
+    Html,
+}
+
+impl OutputFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Ansi => "ans",
+            OutputFormat::Text => "txt",
+            OutputFormat::Html => "html",
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ansi" | "ans" => Ok(OutputFormat::Ansi),
+            "text" | "txt" => Ok(OutputFormat::Text),
+            "html" => Ok(OutputFormat::Html),
+            other => Err(format!(
+                "unknown output format `{other}`, expected ansi, text or html"
+            )),
+        }
+    }
+}
+
+/// An image to convert.
+/// The width of the written files (in columns), when there's no size option.
+const DEFAULT_FILE_WIDTH: u32 = 100;
+
+#[derive(Debug)]
+struct Input {
+    path: PathBuf,
+    /// The path relative to the input directory (or just the file name), used for the `{path}` placeholder.
+    relative: PathBuf,
+}
+
+/// Check if the inputs need the batch mode (multiple images, directories or globs).
+pub fn is_batch(opts: &Opts) -> bool {
+    opts.output_dir.is_some()
+        || opts.paths.len() > 1
+        || opts
+            .paths
+            .first()
+            .is_some_and(|p| Path::new(p).is_dir() || is_glob(p))
+}
+
+/// Convert all the inputs, and report the failed ones without aborting.
+///
+/// Return the number of the failed files.
+pub fn run(opts: &Opts, config: &Config) -> usize {
+    let (inputs, mut failed) = collect_inputs(&opts.paths);
+    for (path, e) in &failed {
+        eprintln!("{path}: {e}");
+    }
+
+    let mut config = match opts.format {
+        OutputFormat::Ansi => config.clone(),
+        OutputFormat::Text => config.clone().with_flags(config.flags & !COLORS),
+        OutputFormat::Html => config.clone().with_flags(config.flags | COLORS),
+    };
+    // The files shouldn't depend on the terminal that converted them.
+    if opts.output_dir.is_some() && opts.depth.is_none() {
+        config = config.with_color_depth(ColorDepth::TrueColor);
+    }
+
+    let Some(dir) = &opts.output_dir else {
+        // Print them in order.
+        let results: Vec<_> = inputs
+            .par_iter()
+            .map(|input| convert(opts, &config, &input.path, false))
+            .collect();
+        let mut out = io::stdout().lock();
+        for (input, res) in inputs.iter().zip(results) {
+            let res = res.and_then(|buf| {
+                if inputs.len() > 1 {
+                    writeln!(out, "==> {} <==", input.path.display())?;
+                }
+                out.write_all(&buf)?;
+                Ok(())
+            });
+            if let Err(e) = res {
+                eprintln!("{}: {e}", input.path.display());
+                failed.push((input.path.display().to_string(), e.to_string()));
+            }
+        }
+        return failed.len();
+    };
+
+    // Resolve the output paths first, so two inputs can't overwrite each other.
+    let mut outputs: HashMap<PathBuf, &Path> = HashMap::new();
+    let mut jobs = Vec::with_capacity(inputs.len());
+    for input in &inputs {
+        let output = match output_path(&opts.name_template, input, opts.format) {
+            Ok(output) => dir.join(output),
+            Err(e) => {
+                eprintln!("{e}");
+                return inputs.len() + failed.len();
+            }
+        };
+        if let Some(other) = outputs.insert(output.clone(), &input.path) {
+            let e = format!(
+                "the output {} is already used by {}",
+                output.display(),
+                other.display()
+            );
+            eprintln!("{}: {e}", input.path.display());
+            failed.push((input.path.display().to_string(), e));
+            continue;
+        }
+        jobs.push((input, output));
+    }
+
+    let errors: Vec<_> = jobs
+        .par_iter()
+        .filter_map(|(input, output)| {
+            let res = convert(opts, &config, &input.path, true).and_then(|buf| {
+                if let Some(parent) = output.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                let mut file = BufWriter::new(File::create(output)?);
+                file.write_all(&buf)?;
+                file.flush()?;
+                Ok(())
+            });
+            res.err()
+                .map(|e| (input.path.display().to_string(), e.to_string()))
+        })
+        .collect();
+    for (path, e) in &errors {
+        eprintln!("{path}: {e}");
+    }
+    eprintln!(
+        "Converted {} of {} files into {}",
+        jobs.len() - errors.len(),
+        inputs.len(),
+        dir.display()
+    );
+    failed.extend(errors);
+    failed.len()
+}
+
+/// Tile the inputs in a grid, and print it.
+///
+/// Return the number of the failed files.
+pub fn montage(opts: &Opts, config: &Config) -> usize {
+    const DEFAULT_CELL_WIDTH: u32 = 20;
+
+    let (inputs, mut failed) = collect_inputs(&opts.paths);
+
+    // Fill the terminal width, unless the cell width is specified.
+    let width = opts.width.unwrap_or_else(|| {
+        terminal_size().map_or(DEFAULT_CELL_WIDTH, |(cols, _)| {
+            let gutters = (opts.columns - 1) * opts.gutter;
+            ((cols as usize).saturating_sub(gutters) / opts.columns).max(1) as u32
+        })
+    });
+    let cell = (Some(width), opts.height);
+    let fit = opts.fit.unwrap_or(Fit::Contain);
+
+    let images: Vec<_> = inputs
+        .par_iter()
+        .map(|input| -> Result<_, ImageError> {
+            let image = transform(opts, image::open(&input.path)?);
+            let (w, h) = fit_size(fit, image.dimensions(), cell).expect("the cell width is known");
+            Ok(resample(&image, (w, h), opts.sampler).to_text(config.clone()))
+        })
+        .collect();
+
+    let mut montage = Montage::new(opts.columns).with_gutter(opts.gutter, opts.gutter / 2);
+    for (input, image) in inputs.iter().zip(images) {
+        match image {
+            Ok(image) => {
+                let caption = opts
+                    .captions
+                    .then(|| input.path.file_name().unwrap_or_default().to_string_lossy());
+                montage.push(image, caption);
+            }
+            Err(e) => failed.push((input.path.display().to_string(), e.to_string())),
+        }
+    }
+    for (path, e) in &failed {
+        eprintln!("{path}: {e}");
+    }
+
+    let mut out = io::stdout().lock();
+    if let Err(e) = write!(out, "{montage}") {
+        eprintln!("Can't write the output: {e}");
+        return inputs.len();
+    }
+    failed.len()
+}
+
+/// Decode, transform, resize and convert an image into the output format.
+///
+/// The images that are written `to_file` get a fixed width when there's no size, instead of fitting the terminal.
+fn convert(
+    opts: &Opts,
+    config: &Config,
+    path: &Path,
+    to_file: bool,
+) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let image = transform(opts, image::open(path)?);
+    let image = if to_file && opts.width.is_none() && opts.height.is_none() && opts.scale.is_none()
+    {
+        let size = Fit::Width.fit(
+            image.dimensions(),
+            (DEFAULT_FILE_WIDTH, u32::MAX),
+            CELL_ASPECT,
+        );
+        resample(&image, size, opts.sampler)
+    } else {
+        resize(opts, image)
+    };
+    let mut buf = Vec::with_capacity(config.calc_buf_size(image.width(), image.height()));
+    let res = if opts.format == OutputFormat::Html {
+        let title = path.file_name().unwrap_or_default().to_string_lossy();
+        write_html(config, &image, &title, &mut buf)
+    } else {
+        convert_image_to_ascii(config, &image, &mut buf)
+    };
+    res.map_err(|e| e.to_string())?;
+    Ok(buf)
+}
+
+/// Expand the directories and the globs, the errors are returned with their inputs.
+fn collect_inputs(patterns: &[String]) -> (Vec<Input>, Vec<(String, String)>) {
+    let mut inputs = Vec::new();
+    let mut errors = Vec::new();
+    for pattern in patterns {
+        let path = Path::new(pattern);
+        if path.is_dir() {
+            if let Err(e) = walk(path, path, &mut inputs) {
+                errors.push((pattern.clone(), e.to_string()));
+            }
+        } else if !path.exists() && is_glob(pattern) {
+            let paths = match glob::glob(pattern) {
+                Ok(paths) => paths,
+                Err(e) => {
+                    errors.push((pattern.clone(), e.to_string()));
+                    continue;
+                }
+            };
+            let mut matched = false;
+            for path in paths {
+                matched = true;
+                match path {
+                    Ok(path) if path.is_dir() => {
+                        if let Err(e) = walk(&path, &path, &mut inputs) {
+                            errors.push((path.display().to_string(), e.to_string()));
+                        }
+                    }
+                    Ok(path) => inputs.push(Input {
+                        relative: file_name(&path),
+                        path,
+                    }),
+                    Err(e) => errors.push((e.path().display().to_string(), e.to_string())),
+                }
+            }
+            if !matched {
+                errors.push((pattern.clone(), "no matches".to_string()));
+            }
+        } else {
+            inputs.push(Input {
+                relative: file_name(path),
+                path: path.to_path_buf(),
+            });
+        }
+    }
+    (inputs, errors)
+}
+
+/// Collect the images in a directory recursively, sorted by their paths.
+fn walk(root: &Path, dir: &Path, inputs: &mut Vec<Input>) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            walk(root, &path, inputs)?;
+        } else if ImageFormat::from_path(&path).is_ok() {
+            inputs.push(Input {
+                relative: path.strip_prefix(root).unwrap_or(&path).to_path_buf(),
+                path,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[inline]
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+#[inline]
+fn file_name(path: &Path) -> PathBuf {
+    path.file_name().map(PathBuf::from).unwrap_or_default()
+}
+
+/// Fill the name template placeholders: `{path}` (the relative path without the extension), `{stem}`, `{name}`
+/// and `{ext}` (the output format extension).
+fn output_path(template: &str, input: &Input, format: OutputFormat) -> Result<PathBuf, String> {
+    let stem = input.path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = input.path.file_name().unwrap_or_default().to_string_lossy();
+    let path = input.relative.with_extension("");
+
+    let mut res = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        res.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| format!("Unclosed placeholder in the name template `{template}`"))?;
+        match &rest[start + 1..start + end] {
+            "path" => res.push_str(&path.to_string_lossy()),
+            "stem" => res.push_str(&stem),
+            "name" => res.push_str(&name),
+            "ext" => res.push_str(format.extension()),
+            other => {
+                return Err(format!(
+                    "Unknown placeholder `{{{other}}}` in the name template, expected {{path}}, {{stem}}, {{name}} or {{ext}}"
+                ))
+            }
+        }
+        rest = &rest[start + end + 1..];
+    }
+    res.push_str(rest);
+    Ok(PathBuf::from(res))
+}
+
+fn write_html(
+    config: &Config,
+    image: &DynamicImage,
+    title: &str,
+    out: &mut Vec<u8>,
+) -> Result<(), Box<dyn Error>> {
+    let mut html = Html::default();
+    convert_image_to_ascii(config, image, &mut html)?;
+    write!(
+        out,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n",
+        escape(title)
+    )?;
+    let background = html
+        .background
+        .take()
+        .map(|c| format!(" background-color: {c};"))
+        .unwrap_or_default();
+    write!(
+        out,
+        "<pre style=\"font-family: monospace; line-height: 1;{background}\">\n{}</pre>\n</body>\n</html>\n",
+        html.finish()
+    )?;
+    Ok(())
+}
+
+/// Write the fragments as HTML spans, the consecutive fragments with the same colors share the same span.
+#[derive(Debug, Default)]
+struct Html {
+    body: String,
+    background: Option<String>,
+    /// The style of the current open span.
+    style: Option<String>,
+}
+
+impl Html {
+    fn push(&mut self, sym: char, style: Option<String>) {
+        if self.style != style {
+            if self.style.is_some() {
+                self.body.push_str("</span>");
+            }
+            if let Some(style) = &style {
+                let _ = write!(self.body, "<span style=\"{style}\">");
+            }
+            self.style = style;
+        }
+        self.body.push_str(&escape(&sym.to_string()));
+    }
+
+    fn close(&mut self) {
+        if self.style.take().is_some() {
+            self.body.push_str("</span>");
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.close();
+        self.body
+    }
+}
+
+impl FragmentWriter for Html {
+    fn background(&mut self, bc: &ANSIColor) -> Result<bool, Box<dyn Error>> {
+        self.background = css_color(bc);
+        Ok(true)
+    }
+
+    fn write_fragment(&mut self, info: FragmentInfo) -> Result<(), Box<dyn Error>> {
+        self.push(info.sym, None);
+        Ok(())
+    }
+
+    fn write_colored_fragment(
+        &mut self,
+        info: FragmentInfo,
+        bc: Option<&ANSIColor>,
+        fc: Option<&ANSIColor>,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut style = String::new();
+        if let Some(fc) = fc.and_then(css_color) {
+            let _ = write!(style, "color: {fc};");
+        }
+        if let Some(bc) = bc.and_then(css_color) {
+            let _ = write!(style, "background-color: {bc};");
+        }
+        self.push(info.sym, (!style.is_empty()).then_some(style));
+        Ok(())
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Box<dyn Error>> {
+        // Only the new lines matters, the rest are the ANSI escape codes.
+        if bytes == b"\n" {
+            self.close();
+            self.body.push('\n');
+        }
+        Ok(())
+    }
+}
+
+fn css_color(color: &ANSIColor) -> Option<String> {
+    color
+        .rgb()
+        .map(|(r, g, b)| format!("#{r:02x}{g:02x}{b:02x}"))
+}
+
+fn escape(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '&' => res.push_str("&amp;"),
+            '"' => res.push_str("&quot;"),
+            c => res.push(c),
+        }
+    }
+    res
+}
@@ -0,0 +1,372 @@
+// This file is shared with the build script (to generate the shell completions and the man page),
+// so it should only depend on `clap` and `std`, the values get parsed into the `aarty` types in `args.rs`.
+
+use std::num::NonZeroU8;
+
+use clap::{ArgAction, Parser, Subcommand};
+
+/// Mini-framework to display pictures on your terminal
+#[derive(Debug, Parser)]
+#[command(
+    name = "aarty",
+    version,
+    about,
+    disable_help_flag = true,
+    disable_version_flag = true
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// The images to convert to ASCII art, reads the stdin if it's not specified.
+    /// The directories are converted recursively, and the globs (e.g. 'thumbs/*.png') are expanded
+    #[arg(value_name = "PATH", value_hint = clap::ValueHint::AnyPath)]
+    pub paths: Vec<String>,
+
+    /// The characters to draw the image with (from the darker pixels to the lighter ones, e.g. " .:#"), one character is enough with the colors mode
+    #[arg(
+        short = 'c',
+        long = "chars",
+        visible_alias = "sympols",
+        value_name = "CHARS",
+        global = true
+    )]
+    pub sym_set: Option<String>,
+    /// Use a built-in characters set
+    #[arg(
+        short = 'P',
+        long,
+        value_name = "NAME",
+        value_parser = ["default", "standard", "bourke", "blocks", "dots", "digits", "katakana", "moons"],
+        conflicts_with = "sym_set",
+        global = true
+    )]
+    pub preset: Option<String>,
+    /// The config file, defaults to `$XDG_CONFIG_HOME/aarty/config.toml`
+    #[arg(long, value_name = "FILE", value_hint = clap::ValueHint::FilePath, global = true)]
+    pub config: Option<String>,
+    /// Use a named profile from the config file, the command line options override it
+    #[arg(short, long, value_name = "NAME", global = true)]
+    pub profile: Option<String>,
+    /// Re-render the image whenever the file changes, until ctrl+c
+    #[arg(
+        long,
+        conflicts_with_all = ["animate", "y4m", "raw", "record", "output_dir", "montage"]
+    )]
+    pub watch: bool,
+
+    /// The output scale (1 is the original size), the image fits the terminal when there's no scale, width or height
+    #[arg(
+        short,
+        long,
+        value_name = "SCALE",
+        help_heading = "Size",
+        conflicts_with_all = ["width", "height", "fit"]
+    )]
+    pub scale: Option<NonZeroU8>,
+    /// The output width in columns
+    #[arg(
+        short,
+        long,
+        visible_alias = "cols",
+        value_name = "COLUMNS",
+        help_heading = "Size"
+    )]
+    pub width: Option<u32>,
+    /// The output height in rows
+    #[arg(
+        short,
+        long,
+        visible_alias = "rows",
+        value_name = "ROWS",
+        help_heading = "Size"
+    )]
+    pub height: Option<u32>,
+    /// How to fit the image into the terminal (or into the width and height if they're specified)
+    #[arg(
+        short,
+        long,
+        value_name = "MODE",
+        value_parser = ["width", "height", "contain", "cover"],
+        help_heading = "Size"
+    )]
+    pub fit: Option<String>,
+    /// The scaling filter [default: nearest]
+    #[arg(
+        short = 'F',
+        long,
+        value_name = "FILTER",
+        value_parser = ["nearest", "box", "bilinear", "lanczos3"],
+        help_heading = "Size"
+    )]
+    pub filter: Option<String>,
+
+    /// Crop the image to a rectangle before anything else, the offset defaults to the top left corner
+    #[arg(
+        long,
+        value_name = "WxH[+X+Y]",
+        help_heading = "Transform",
+        global = true
+    )]
+    pub crop: Option<String>,
+    /// Rotate the image clockwise by the degrees (after the crop)
+    #[arg(
+        long,
+        value_name = "DEGREES",
+        value_parser = ["90", "180", "270"],
+        help_heading = "Transform",
+        global = true
+    )]
+    pub rotate: Option<String>,
+    /// Mirror the image horizontally (after the rotation)
+    #[arg(long, help_heading = "Transform", global = true)]
+    pub flip_h: bool,
+    /// Mirror the image vertically (after the rotation)
+    #[arg(long, help_heading = "Transform", global = true)]
+    pub flip_v: bool,
+
+    /// Use colors
+    #[arg(
+        short = 'u',
+        long = "colors",
+        visible_alias = "color",
+        overrides_with = "no_colors",
+        help_heading = "Colors",
+        global = true
+    )]
+    pub colors: bool,
+    /// Don't use colors, even if the terminal supports them
+    #[arg(
+        long = "no-colors",
+        visible_alias = "no-color",
+        overrides_with = "colors",
+        help_heading = "Colors",
+        global = true
+    )]
+    pub no_colors: bool,
+    /// Reverse the foreground color with the background
+    #[arg(
+        short,
+        long,
+        overrides_with = "no_reverse",
+        help_heading = "Colors",
+        global = true
+    )]
+    pub reverse: bool,
+    /// Don't reverse the colors, even if the config file does
+    #[arg(
+        long,
+        overrides_with = "reverse",
+        help_heading = "Colors",
+        global = true
+    )]
+    pub no_reverse: bool,
+    /// Paint the cells with the pixels colors, and draw the details over them in black or white (implies --colors)
+    #[arg(
+        long,
+        overrides_with = "no_cell_colors",
+        help_heading = "Colors",
+        global = true
+    )]
+    pub cell_colors: bool,
+    /// Don't paint the cells with the pixels colors, even if the config file does
+    #[arg(
+        long,
+        overrides_with = "cell_colors",
+        help_heading = "Colors",
+        global = true
+    )]
+    pub no_cell_colors: bool,
+    /// The background color (#rgb, #rrggbb, rgb(r, g, b), hsl(h, s%, l%), a CSS color name or a palette index)
+    #[arg(
+        short,
+        long,
+        visible_alias = "back",
+        value_name = "COLOR",
+        help_heading = "Colors",
+        global = true
+    )]
+    pub background: Option<String>,
+    /// How to draw the transparent pixels: blend them over the background (or leave them to the terminal background),
+    /// over a checkerboard, or ignore the alpha [default: blend]
+    #[arg(
+        long,
+        value_name = "MODE",
+        value_parser = ["blend", "checkerboard", "ignore"],
+        help_heading = "Colors",
+        global = true
+    )]
+    pub alpha: Option<String>,
+    /// Override the detected color depth
+    #[arg(
+        short,
+        long,
+        value_name = "DEPTH",
+        value_parser = ["truecolor", "24bit", "256", "16"],
+        help_heading = "Colors",
+        global = true
+    )]
+    pub depth: Option<String>,
+    /// Display the image with a terminal graphics protocol instead of text,
+    /// `auto` picks the best one that the terminal supports [default: off]
+    #[arg(
+        short,
+        long,
+        value_name = "PROTOCOL",
+        value_parser = ["auto", "off", "none", "kitty", "sixel"],
+        help_heading = "Colors"
+    )]
+    pub graphics: Option<String>,
+
+    /// Add to the brightness, from -1 (black) to 1 (white) [default: 0]
+    #[arg(
+        long,
+        value_name = "AMOUNT",
+        allow_negative_numbers = true,
+        help_heading = "Adjustments",
+        global = true
+    )]
+    pub brightness: Option<f32>,
+    /// Multiply the contrast, 0 is all gray [default: 1]
+    #[arg(
+        long,
+        value_name = "FACTOR",
+        help_heading = "Adjustments",
+        global = true
+    )]
+    pub contrast: Option<f32>,
+    /// The gamma correction, above 1 brightens the dark tones [default: 1]
+    #[arg(
+        long,
+        value_name = "GAMMA",
+        help_heading = "Adjustments",
+        global = true
+    )]
+    pub gamma: Option<f32>,
+    /// Multiply the saturation, 0 is grayscale [default: 1]
+    #[arg(
+        long,
+        value_name = "FACTOR",
+        help_heading = "Adjustments",
+        global = true
+    )]
+    pub saturation: Option<f32>,
+    /// Invert the colors
+    #[arg(
+        long,
+        overrides_with = "no_invert",
+        help_heading = "Adjustments",
+        global = true
+    )]
+    pub invert: bool,
+    /// Don't invert the colors, even if the config file does
+    #[arg(
+        long,
+        overrides_with = "invert",
+        help_heading = "Adjustments",
+        global = true
+    )]
+    pub no_invert: bool,
+    /// How the brightness is mapped to the characters, `equalize` and `clahe` spread low-contrast images
+    /// over all the characters (clahe takes optional regions count, up to 64, and clip limit, e.g. clahe:8:2.5) [default: absolute]
+    #[arg(long, value_name = "MODE", help_heading = "Adjustments", global = true)]
+    pub levels: Option<String>,
+
+    /// Play all the frames of an animated image (GIF, APNG, WebP)
+    #[arg(short, long, help_heading = "Animations and streams", conflicts_with_all = ["y4m", "raw"])]
+    pub animate: bool,
+    /// How many times to play the animation, 0 means forever [default: 0]
+    #[arg(
+        short,
+        long = "loops",
+        visible_alias = "loop",
+        value_name = "N",
+        help_heading = "Animations and streams"
+    )]
+    pub loops: Option<u32>,
+    /// Read a YUV4MPEG2 stream
+    #[arg(long, help_heading = "Animations and streams", conflicts_with = "raw")]
+    pub y4m: bool,
+    /// Read a raw rgb24 stream with the given frame size
+    #[arg(long, value_name = "WxH", help_heading = "Animations and streams")]
+    pub raw: Option<String>,
+    /// The stream frame rate, overrides the YUV4MPEG2 header
+    #[arg(long, value_name = "FPS", help_heading = "Animations and streams")]
+    pub fps: Option<f64>,
+    /// Record the animation (or the stream) into an asciicast v2 file instead of playing it
+    #[arg(
+        long,
+        value_name = "FILE",
+        value_hint = clap::ValueHint::FilePath,
+        help_heading = "Animations and streams"
+    )]
+    pub record: Option<String>,
+
+    /// Write the converted images into this directory instead of the stdout
+    #[arg(
+        short,
+        long,
+        value_name = "DIR",
+        value_hint = clap::ValueHint::DirPath,
+        help_heading = "Batch",
+        conflicts_with_all = ["animate", "y4m", "raw", "record"]
+    )]
+    pub output_dir: Option<String>,
+    /// The output files names, {path} is the input path relative to its directory without the extension,
+    /// {stem} and {name} are the input file name without and with its extension, and {ext} is the format extension
+    #[arg(
+        long = "name",
+        value_name = "TEMPLATE",
+        default_value = "{path}.{ext}",
+        help_heading = "Batch",
+        requires = "output_dir"
+    )]
+    pub name_template: String,
+    /// The output format [default: ansi]
+    #[arg(
+        short = 'O',
+        long,
+        value_name = "FORMAT",
+        value_parser = ["ansi", "text", "html"],
+        help_heading = "Batch"
+    )]
+    pub format: Option<String>,
+
+    /// Tile the images in a grid (a contact sheet)
+    #[arg(
+        short,
+        long,
+        help_heading = "Montage",
+        conflicts_with_all = ["output_dir", "format", "animate", "y4m", "raw", "record"]
+    )]
+    pub montage: bool,
+    /// The montage columns number [default: 4]
+    #[arg(long, value_name = "N", help_heading = "Montage", requires = "montage")]
+    pub columns: Option<NonZeroU8>,
+    /// The spaces between the montage columns (and the empty lines between its rows) [default: 2]
+    #[arg(long, value_name = "N", help_heading = "Montage", requires = "montage")]
+    pub gutter: Option<u8>,
+    /// Write the file names under the images
+    #[arg(long, help_heading = "Montage", requires = "montage")]
+    pub captions: bool,
+
+    /// Print help
+    #[arg(long, action = ArgAction::Help, global = true)]
+    pub help: Option<bool>,
+    /// Print version
+    #[arg(short = 'v', long, action = ArgAction::Version)]
+    pub version: Option<bool>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Browse the images in an interactive full-screen viewer
+    ///
+    /// Keys: arrows or hjkl to pan, +/- to zoom, 0 to fit, n/p to switch the image,
+    /// c to toggle the colors, r to toggle the reverse mode, s to switch the characters preset, and q to quit
+    View {
+        /// The images to view
+        #[arg(value_name = "PATH", value_hint = clap::ValueHint::FilePath, required = true)]
+        paths: Vec<String>,
+    },
+}
@@ -0,0 +1,173 @@
+use std::{collections::HashMap, env, fs, num::NonZeroU8, path::PathBuf};
+
+use serde::{Deserialize, Deserializer};
+
+/// The config file.
+///
+/// ```toml
+/// [defaults]
+/// chars = " .,-~!;:=*&%$@#"
+/// colors = true
+/// filter = "lanczos3"
+///
+/// [profiles.mono]
+/// colors = false
+/// preset = "blocks"
+///
+/// [profiles.night]
+/// gamma = 1.8
+/// levels = "clahe"
+///
+/// [profiles.peach]
+/// background = "#ffd0bb"
+/// reverse = true
+/// depth = 256
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    /// The options that used with every invocation
+    pub defaults: Profile,
+    /// The named profiles, they override the defaults
+    pub profiles: HashMap<String, Profile>,
+}
+
+/// A set of options, every option can be overridden by the command line.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub chars: Option<String>,
+    pub preset: Option<String>,
+    pub scale: Option<NonZeroU8>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fit: Option<String>,
+    pub filter: Option<String>,
+    pub colors: Option<bool>,
+    pub reverse: Option<bool>,
+    pub cell_colors: Option<bool>,
+    pub background: Option<String>,
+    #[serde(deserialize_with = "string_or_number")]
+    pub depth: Option<String>,
+    pub graphics: Option<String>,
+    pub format: Option<String>,
+    pub loops: Option<u32>,
+    pub fps: Option<f64>,
+    pub brightness: Option<f32>,
+    pub contrast: Option<f32>,
+    pub gamma: Option<f32>,
+    pub saturation: Option<f32>,
+    pub invert: Option<bool>,
+    pub levels: Option<String>,
+    pub alpha: Option<String>,
+}
+
+impl ConfigFile {
+    /// Load the config file from the given path, or from the default path if it exists.
+    pub fn load(path: Option<&str>) -> Result<Self, String> {
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => match default_path() {
+                Some(path) if path.is_file() => path,
+                _ => return Ok(Self::default()),
+            },
+        };
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Can't read the config file {}: {e}", path.display()))?;
+        toml::from_str(&content)
+            .map_err(|e| format!("Can't parse the config file {}: {e}", path.display()))
+    }
+
+    /// The defaults, overridden by the profile if it's specified.
+    pub fn profile(mut self, name: Option<&str>) -> Result<Profile, String> {
+        let Some(name) = name else {
+            return Ok(self.defaults);
+        };
+        let profile = self.profiles.remove(name).ok_or_else(|| {
+            let mut names: Vec<_> = self.profiles.keys().map(String::as_str).collect();
+            names.sort_unstable();
+            format!(
+                "Unknown profile {name}, the available profiles are: {}",
+                names.join(", ")
+            )
+        })?;
+        Ok(profile.or(self.defaults))
+    }
+}
+
+impl Profile {
+    /// Fill the missing options from `base`.
+    ///
+    /// The options that conflict with the ones that are set here are dropped from `base`,
+    /// `chars` replaces the base `preset` (and the reverse), and `scale` replaces the base
+    /// `width`, `height` and `fit` (and the reverse).
+    ///
+    /// ```ignore
+    /// let profile = Profile { preset: Some("blocks".into()), ..Default::default() };
+    /// let base = Profile { chars: Some(" .#".into()), width: Some(80), ..Default::default() };
+    /// let merged = Profile { scale: NonZeroU8::new(2), ..profile }.or(base);
+    /// assert_eq!((merged.chars, merged.preset), (None, Some("blocks".into())));
+    /// assert_eq!((merged.scale, merged.width), (NonZeroU8::new(2), None));
+    /// ```
+    pub fn or(self, mut base: Profile) -> Profile {
+        if self.chars.is_some() || self.preset.is_some() {
+            (base.chars, base.preset) = (None, None);
+        }
+        if self.scale.is_some() {
+            (base.width, base.height, base.fit) = (None, None, None);
+        }
+        if self.width.is_some() || self.height.is_some() || self.fit.is_some() {
+            base.scale = None;
+        }
+        Profile {
+            chars: self.chars.or(base.chars),
+            preset: self.preset.or(base.preset),
+            scale: self.scale.or(base.scale),
+            width: self.width.or(base.width),
+            height: self.height.or(base.height),
+            fit: self.fit.or(base.fit),
+            filter: self.filter.or(base.filter),
+            colors: self.colors.or(base.colors),
+            reverse: self.reverse.or(base.reverse),
+            cell_colors: self.cell_colors.or(base.cell_colors),
+            background: self.background.or(base.background),
+            depth: self.depth.or(base.depth),
+            graphics: self.graphics.or(base.graphics),
+            format: self.format.or(base.format),
+            loops: self.loops.or(base.loops),
+            fps: self.fps.or(base.fps),
+            brightness: self.brightness.or(base.brightness),
+            contrast: self.contrast.or(base.contrast),
+            gamma: self.gamma.or(base.gamma),
+            saturation: self.saturation.or(base.saturation),
+            invert: self.invert.or(base.invert),
+            levels: self.levels.or(base.levels),
+            alpha: self.alpha.or(base.alpha),
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/aarty/config.toml`, or `~/.config/aarty/config.toml`.
+fn default_path() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+    Some(dir.join("aarty").join("config.toml"))
+}
+
+/// Accept the numeric values as strings (e.g. `depth = 256`).
+fn string_or_number<'de, D: Deserializer<'de>>(d: D) -> Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Value {
+        String(String),
+        Number(u32),
+    }
+
+    Ok(Option::<Value>::deserialize(d)?.map(|v| match v {
+        Value::String(s) => s,
+        Value::Number(n) => n.to_string(),
+    }))
+}
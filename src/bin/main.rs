@@ -3,16 +3,35 @@
 compile_error!("Needs `image` feature to compile the binary!");
 
 use std::{
-    io::{self, BufWriter, Cursor, Read},
+    error::Error,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Cursor, IsTerminal, Read, Write},
     process,
+    time::Duration,
 };
 
-use aarty::{convert_image_to_ascii, Config};
-use image::{io::Reader, GenericImageView};
+use aarty::{
+    animation::{self, Animation, AnimationFrame, Player, Repeat},
+    asciicast::Recorder,
+    capabilities::Capabilities,
+    convert_image_to_ascii,
+    graphics::Graphics,
+    resample::Sampler,
+    terminal::{cell_size, terminal_size, Fit, CELL_ASPECT},
+    video::{RawRgbReader, VideoFrame, Y4mReader},
+    view::{Crop, FlipH, FlipV, Rotate90},
+    viewer::{Keys, Viewer},
+    Config, ToTextImage,
+};
+use image::{io::Reader, DynamicImage, GenericImageView, ImageFormat, RgbImage, RgbaImage};
 
-use crate::args::Opts;
+use crate::args::{GraphicsMode, Opts};
 
 mod args;
+mod batch;
+mod cli;
+mod config;
+mod watch;
 
 const G_ERR: i32 = 1;
 const IO_ERR: i32 = 2;
@@ -28,7 +47,78 @@ fn main() {
         }
     };
 
-    let Ok(image) = (if let Some(path) = opts.path.as_ref() {
+    let mut config = Config::new(opts.sym_set.clone())
+        .with_flags(opts.flags)
+        .with_adjustments(opts.adjustments)
+        .with_levels(opts.levels)
+        .with_alpha(opts.alpha);
+
+    if let Some(background) = &opts.background {
+        config = config.with_background(background.clone());
+    }
+
+    // Only detect the terminal capabilities if we're writing to it.
+    let caps = io::stdout().is_terminal().then(Capabilities::detect);
+    if let Some(caps) = &caps {
+        if config.use_colors() {
+            config = config.with_color_depth(caps.colors.unwrap_or_default());
+        } else if opts.auto_colors {
+            config = caps.apply(config);
+        }
+    }
+    if let Some(depth) = opts.depth {
+        config = config.with_color_depth(depth);
+    }
+    let graphics = match opts.graphics {
+        GraphicsMode::Auto => caps.and_then(|caps| caps.graphics()),
+        GraphicsMode::Off => None,
+        GraphicsMode::Use(graphics) => Some(graphics),
+    };
+
+    if opts.view {
+        view(&opts, config);
+        return;
+    }
+
+    if opts.watch {
+        let path = opts.path().expect("the watch mode needs a path");
+        if let Err(e) = watch::watch(&opts, &config, path) {
+            eprintln!("Can't write the output: {e}");
+            process::exit(IO_ERR);
+        }
+        return;
+    }
+
+    if opts.animate {
+        animate(&opts, &config);
+        return;
+    }
+
+    if opts.montage {
+        let failed = batch::montage(&opts, &config);
+        if failed > 0 {
+            process::exit(OP_ERR);
+        }
+        return;
+    }
+
+    if batch::is_batch(&opts) {
+        let failed = batch::run(&opts, &config);
+        if failed > 0 {
+            process::exit(OP_ERR);
+        }
+        return;
+    }
+
+    if opts.y4m || opts.raw.is_some() {
+        if let Err(e) = stream(&opts, &config) {
+            eprintln!("Can't play the stream: {e}");
+            process::exit(IO_ERR);
+        }
+        return;
+    }
+
+    let Ok(image) = (if let Some(path) = opts.path() {
         match Reader::open(path) {
             Ok(r) => r,
             Err(e) => {
@@ -38,13 +128,7 @@ fn main() {
         }
         .decode()
     } else {
-        const CAPACITY: usize = 2 * 1048576; // 2mb
-        let mut buf = Vec::with_capacity(CAPACITY);
-        if let Err(e) = io::stdin().lock().read_to_end(&mut buf) {
-            eprintln!("{e}");
-            process::exit(IO_ERR);
-        }
-        let Ok(reader) = Reader::new(Cursor::new(buf)).with_guessed_format() else {
+        let Ok(reader) = Reader::new(Cursor::new(read_stdin())).with_guessed_format() else {
             eprintln!("Can't read the input");
             process::exit(IO_ERR);
         };
@@ -53,34 +137,334 @@ fn main() {
         eprintln!("Failed to guess the input format or the input format was unsportted");
         process::exit(OP_ERR);
     };
+    let image = transform(&opts, image);
+
+    if let Some(graphics) = graphics {
+        if let Err(e) = display_graphics(&opts, graphics, image) {
+            eprintln!("Can't write the output: {e}");
+            process::exit(IO_ERR);
+        }
+        return;
+    }
+
+    let image = resize(&opts, image);
+    let (w, h) = image.dimensions();
+
+    let buf_size = config.calc_buf_size(w, h);
+
+    let mut out = BufWriter::with_capacity(buf_size, Box::new(io::stdout().lock()));
+
+    if let Err(e) = convert_image_to_ascii(&config, &image, &mut out) {
+        eprintln!("Can't write the output: {e}");
+        process::exit(IO_ERR);
+    }
+}
+
+/// Crop, rotate and flip the image, in this order.
+pub(crate) fn transform(opts: &Opts, image: DynamicImage) -> DynamicImage {
+    if opts.crop.is_none() && opts.rotate == 0 && !opts.flip_h && !opts.flip_v {
+        return image;
+    }
+
+    use aarty::PixelImage;
+
+    let mut view: &dyn PixelImage = &image;
+    let crop;
+    if let Some((x, y, w, h)) = opts.crop {
+        crop = Crop::new(view, x, y, w, h);
+        match crop.dimensions() {
+            (0, _) | (_, 0) => {
+                let (w, h) = PixelImage::dimensions(&image);
+                eprintln!("The crop rectangle is out of the {w}x{h} image, ignoring it");
+            }
+            _ => view = &crop,
+        }
+    }
+    let (quarter, half, three_quarters);
+    if opts.rotate >= 90 {
+        quarter = Rotate90(view);
+        view = &quarter;
+    }
+    if opts.rotate >= 180 {
+        half = Rotate90(view);
+        view = &half;
+    }
+    if opts.rotate >= 270 {
+        three_quarters = Rotate90(view);
+        view = &three_quarters;
+    }
+    let (flip_h, flip_v);
+    if opts.flip_h {
+        flip_h = FlipH(view);
+        view = &flip_h;
+    }
+    if opts.flip_v {
+        flip_v = FlipV(view);
+        view = &flip_v;
+    }
+
+    let (w, h) = view.dimensions();
+    RgbaImage::from_fn(w, h, |x, y| {
+        let p = view.get_pixel(x, y);
+        image::Rgba([p.r, p.g, p.b, p.a])
+    })
+    .into()
+}
+
+pub(crate) fn resize(opts: &Opts, image: DynamicImage) -> DynamicImage {
+    const DEFAULT_SCALE: u32 = 4;
 
     let (mut w, mut h) = image.dimensions();
+    // The width and the height are the output size, stretch the image if both of them are specified.
+    if let (None, Some(width), Some(height)) = (opts.fit, opts.width, opts.height) {
+        return resample(&image, (width, height), opts.sampler);
+    }
+    let fit = opts.fit.or(match (opts.width, opts.height) {
+        (Some(_), _) => Some(Fit::Width),
+        (_, Some(_)) => Some(Fit::Height),
+        _ => opts.scale.is_none().then_some(Fit::Contain),
+    });
+    if let Some(fit) = fit {
+        let term = terminal_size().map(|(cols, rows)| (cols as u32, rows.saturating_sub(1) as u32));
+        let area = match (opts.width, opts.height, term) {
+            (Some(width), Some(height), _) => (Some(width), Some(height)),
+            (width, height, Some((cols, rows))) => {
+                (Some(width.unwrap_or(cols)), Some(height.unwrap_or(rows)))
+            }
+            (width, height, None) => (width, height),
+        };
+        if let Some(size) = fit_size(fit, (w, h), area) {
+            return resample(&image, size, opts.sampler);
+        }
+    }
+
     if let Some(width) = opts.width {
         w = width;
     }
     if let Some(height) = opts.height {
         h = height;
     }
-    let image = if opts.scale.get() > 1 {
-        let scale = opts.scale.get() as u32;
-        image.resize(w / scale, h / scale, opts.sf)
+    let scale = opts.scale.map_or(DEFAULT_SCALE, |s| s.get() as u32);
+    let size = if scale > 1 {
+        // Keep the image aspect ratio inside the scaled size.
+        Fit::Contain.fit(image.dimensions(), (w / scale, h / scale), 1.)
+    } else {
+        (w, h)
+    };
+    resample(&image, size, opts.sampler)
+}
+
+/// Fit the image into the area (in cells), return [`None`] if both of its dimensions are unknown.
+///
+/// With only one known dimension, the image fills that dimension whatever the fit mode is,
+/// since the modes that fill the unknown one would scale the image towards the infinity.
+pub(crate) fn fit_size(
+    fit: Fit,
+    image: (u32, u32),
+    area: (Option<u32>, Option<u32>),
+) -> Option<(u32, u32)> {
+    /// The largest size on each axis, the terminals can't address more cells anyway.
+    const MAX_SIZE: u32 = u16::MAX as u32;
+
+    let (fit, area) = match area {
+        (Some(width), Some(height)) => (fit, (width, height)),
+        (Some(width), None) => (Fit::Width, (width, MAX_SIZE)),
+        (None, Some(height)) => (Fit::Height, (MAX_SIZE, height)),
+        (None, None) => return None,
+    };
+    let (w, h) = fit.fit(image, area, CELL_ASPECT);
+    Some((w.min(MAX_SIZE), h.min(MAX_SIZE)))
+}
+
+/// Resize the image with the built-in filters.
+pub(crate) fn resample(image: &DynamicImage, size: (u32, u32), sampler: Sampler) -> DynamicImage {
+    RgbaImage::from(aarty::resample::resize(image, size, sampler)).into()
+}
+
+fn display_graphics(opts: &Opts, graphics: Graphics, image: DynamicImage) -> io::Result<()> {
+    const DEFAULT_CELL_SIZE: (u16, u16) = (8, 16);
+
+    // The protocols draw the pixels as they are, so the adjustments are applied to the image itself.
+    let image = if opts.adjustments.is_none() {
+        image
     } else {
-        image.resize_exact(w, h, opts.sf)
+        let mut image = image.into_rgba8();
+        for p in image.pixels_mut() {
+            let [r, g, b, a] = p.0;
+            let adjusted = opts.adjustments.apply(aarty::Rgba { r, g, b, a });
+            p.0 = [adjusted.r, adjusted.g, adjusted.b, adjusted.a];
+        }
+        DynamicImage::from(image)
     };
 
-    let mut config = Config::new(opts.sym_set.into()).with_flags(opts.flags);
+    // The size of the image in cells, if it was rendered as text.
+    let (cols, rows) = resize(opts, image.clone()).dimensions();
 
-    if let Some(_) = &opts.background {
-        // TODO: parse the color like `lanterna`
-        config = config.with_background((255, 208, 187));
+    let mut out = BufWriter::new(io::stdout().lock());
+    match graphics {
+        Graphics::Kitty => graphics.write(&image, &mut out, Some((cols, rows)))?,
+        Graphics::Sixel => {
+            let (cw, ch) = cell_size().unwrap_or(DEFAULT_CELL_SIZE);
+            let image = resample(&image, (cols * cw as u32, rows * ch as u32), opts.sampler);
+            graphics.write(&image, &mut out, None)?
+        }
     }
+    writeln!(out)
+}
 
-    let buf_size = config.calc_buf_size(w, h);
+fn view(opts: &Opts, config: Config) {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        eprintln!("The viewer needs an interactive terminal");
+        process::exit(G_ERR);
+    }
 
-    let mut out = BufWriter::with_capacity(buf_size, Box::new(io::stdout().lock()));
+    let mut viewer = Viewer::new(config);
+    for path in &opts.paths {
+        match image::open(path) {
+            Ok(image) => viewer.push(path.as_str(), transform(opts, image)),
+            Err(e) => {
+                eprintln!("{path}: {e}");
+                process::exit(OP_ERR);
+            }
+        }
+    }
 
-    if let Err(e) = convert_image_to_ascii(&config, &image, &mut out) {
+    if let Err(e) = run_viewer(viewer) {
+        eprintln!("Can't run the viewer: {e}");
+        process::exit(IO_ERR);
+    }
+}
+
+#[cfg(unix)]
+fn run_viewer(mut viewer: Viewer<DynamicImage>) -> Result<(), Box<dyn Error>> {
+    let mut screen = aarty::terminal::AlternateScreen::enter(BufWriter::new(io::stdout().lock()))?;
+    let size = || terminal_size().map_or((80, 24), |(cols, rows)| (cols as u32, rows as u32));
+    let keys = Keys::new(io::stdin().lock()).with_pending(aarty::terminal::input_pending);
+    viewer.run(keys, &mut screen, size)
+}
+
+#[cfg(not(unix))]
+fn run_viewer(_: Viewer<DynamicImage>) -> Result<(), Box<dyn Error>> {
+    Err("the viewer is only supported on unix".into())
+}
+
+fn animate(opts: &Opts, config: &Config) {
+    let buf = if let Some(path) = opts.path() {
+        match fs::read(path) {
+            Ok(buf) => buf,
+            Err(e) => {
+                eprintln!("{e}");
+                process::exit(IO_ERR);
+            }
+        }
+    } else {
+        read_stdin()
+    };
+
+    let format = match opts.path().map(ImageFormat::from_path) {
+        Some(Ok(format)) => format,
+        _ => {
+            let Ok(format) = image::guess_format(&buf) else {
+                eprintln!("Failed to guess the input format or the input format was unsportted");
+                process::exit(OP_ERR);
+            };
+            format
+        }
+    };
+
+    let animation = match animation::decode_frames(Cursor::new(buf), format).and_then(|frames| {
+        Animation::from_frames(frames, config, |frame| resize(opts, transform(opts, frame)))
+    }) {
+        Ok(animation) => animation,
+        Err(e) => {
+            eprintln!("Can't decode the animation: {e}");
+            process::exit(OP_ERR);
+        }
+    };
+
+    let res = if let Some(path) = opts.record.as_ref() {
+        record(path, |recorder| {
+            let loops = match opts.loops.into() {
+                Repeat::Infinite => 1,
+                Repeat::Times(n) => n.get(),
+            };
+            for _ in 0..loops {
+                recorder.record(animation.frames())?;
+            }
+            Ok(())
+        })
+    } else {
+        animation.play(io::stdout().lock(), opts.loops.into())
+    };
+
+    if let Err(e) = res {
         eprintln!("Can't write the output: {e}");
         process::exit(IO_ERR);
     }
 }
+
+fn record<F>(path: &str, f: F) -> Result<(), Box<dyn Error>>
+where
+    F: FnOnce(&mut Recorder<BufWriter<File>>) -> Result<(), Box<dyn Error>>,
+{
+    let mut recorder = Recorder::new(BufWriter::new(File::create(path)?));
+    f(&mut recorder)?;
+    recorder.finish()?;
+    Ok(())
+}
+
+fn stream(opts: &Opts, config: &Config) -> Result<(), Box<dyn Error>> {
+    let input: Box<dyn BufRead> = if let Some(path) = opts.path() {
+        Box::new(BufReader::new(File::open(path)?))
+    } else {
+        Box::new(io::stdin().lock())
+    };
+
+    let (frames, mut delay): (Box<dyn Iterator<Item = io::Result<VideoFrame>>>, _) =
+        if let Some((w, h)) = opts.raw {
+            (
+                Box::new(RawRgbReader::new(input, w, h)?),
+                Duration::from_millis(40),
+            )
+        } else {
+            let reader = Y4mReader::new(input)?;
+            let delay = reader.frame_duration();
+            (Box::new(reader), delay)
+        };
+    if let Some(fps) = opts.fps {
+        delay = Duration::from_secs_f64(1. / fps);
+    }
+
+    let frames = frames.map(|frame| -> Result<_, Box<dyn Error>> {
+        let frame = frame?;
+        let (w, h) = aarty::PixelImage::dimensions(&frame);
+        let frame = RgbImage::from_raw(w, h, frame.into_raw()).ok_or("Invalid frame")?;
+        let image = resize(opts, transform(opts, frame.into())).to_text(config.clone());
+        Ok(AnimationFrame::new(image, delay))
+    });
+
+    if let Some(path) = opts.record.as_ref() {
+        return record(path, |recorder| {
+            for frame in frames {
+                recorder.record([frame?])?;
+            }
+            Ok(())
+        });
+    }
+
+    let mut player = Player::new(io::stdout().lock());
+    for frame in frames {
+        player.play([frame?])?;
+    }
+    player.finish()
+}
+
+fn read_stdin() -> Vec<u8> {
+    const CAPACITY: usize = 2 * 1048576; // 2mb
+    let mut buf = Vec::with_capacity(CAPACITY);
+    if let Err(e) = io::stdin().lock().read_to_end(&mut buf) {
+        eprintln!("{e}");
+        process::exit(IO_ERR);
+    }
+    buf
+}
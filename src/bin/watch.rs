@@ -0,0 +1,114 @@
+use std::{
+    error::Error,
+    fs,
+    io::{self, Write},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use aarty::{convert_image_to_ascii, terminal::terminal_size, Config};
+
+use crate::{args::Opts, resize, transform};
+
+/// How often the file is checked.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// What we compare to detect the changes, the terminal size is included so the image is re-fitted when it's resized.
+#[derive(Debug, PartialEq, Eq)]
+struct Stamp {
+    modified: Option<SystemTime>,
+    len: u64,
+    terminal: Option<(u16, u16)>,
+}
+
+impl Stamp {
+    /// [`None`] if the file doesn't exist (e.g. while it's being replaced).
+    fn of(path: &str) -> Option<Self> {
+        let meta = fs::metadata(path).ok()?;
+        Some(Self {
+            modified: meta.modified().ok(),
+            len: meta.len(),
+            terminal: terminal_size(),
+        })
+    }
+}
+
+/// Re-render the image in place whenever the file changes, until ctrl+c.
+pub fn watch(opts: &Opts, config: &Config, path: &str) -> Result<(), Box<dyn Error>> {
+    interrupt::install();
+
+    let mut out = io::stdout().lock();
+    // Hide the cursor while watching.
+    out.write_all(b"\x1b[?25l")?;
+    let res = poll(opts, config, path, &mut out);
+    // Restore the colors and the cursor.
+    out.write_all(b"\x1b[0m\x1b[?25h\n")?;
+    out.flush()?;
+    res
+}
+
+fn poll<W: Write>(
+    opts: &Opts,
+    config: &Config,
+    path: &str,
+    out: &mut W,
+) -> Result<(), Box<dyn Error>> {
+    let mut last = None;
+    while !interrupt::interrupted() {
+        let stamp = Stamp::of(path);
+        if stamp.is_some() && stamp != last {
+            // Render the whole frame before writing it, so the old image doesn't flicker.
+            let mut frame = b"\x1b[H\x1b[2J".to_vec();
+            match image::open(path) {
+                Ok(image) => convert_image_to_ascii(
+                    config,
+                    &resize(opts, transform(opts, image)),
+                    &mut frame,
+                )?,
+                // It may be written right now, try again with the next change.
+                Err(e) => write!(frame, "Can't read {path}: {e}")?,
+            }
+            out.write_all(&frame)?;
+            out.flush()?;
+            last = stamp;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+mod interrupt {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+    extern "C" fn handle(_: libc::c_int) {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+    }
+
+    /// Catch ctrl+c (and `SIGTERM`) instead of exiting, so the terminal can be restored.
+    pub fn install() {
+        let handler = handle as extern "C" fn(libc::c_int);
+        // SAFETY: the handler only stores an atomic, which is async-signal-safe.
+        unsafe {
+            libc::signal(libc::SIGINT, handler as libc::sighandler_t);
+            libc::signal(libc::SIGTERM, handler as libc::sighandler_t);
+        }
+    }
+
+    #[inline]
+    pub fn interrupted() -> bool {
+        INTERRUPTED.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(not(unix))]
+mod interrupt {
+    pub fn install() {}
+
+    #[inline]
+    pub fn interrupted() -> bool {
+        false
+    }
+}
@@ -0,0 +1,278 @@
+use crate::{PixelImage, Rgba};
+
+/// The pixels layout of a raw buffer, every channel is a byte.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum PixelFormat {
+    /// Grayscale.
+    Gray,
+    /// Grayscale with alpha.
+    GrayAlpha,
+    /// Red, green and blue.
+    Rgb,
+    /// Red, green, blue and alpha.
+    Rgba,
+    /// Blue, green and red (e.g. the Windows bitmaps).
+    Bgr,
+    /// Blue, green, red and alpha (e.g. the most of the framebuffers).
+    Bgra,
+}
+
+impl PixelFormat {
+    /// How many bytes a pixel takes.
+    #[inline]
+    pub const fn bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Gray => 1,
+            PixelFormat::GrayAlpha => 2,
+            PixelFormat::Rgb | PixelFormat::Bgr => 3,
+            PixelFormat::Rgba | PixelFormat::Bgra => 4,
+        }
+    }
+
+    #[inline]
+    fn pixel(self, p: &[u8]) -> Rgba {
+        let (r, g, b, a) = match self {
+            PixelFormat::Gray => (p[0], p[0], p[0], u8::MAX),
+            PixelFormat::GrayAlpha => (p[0], p[0], p[0], p[1]),
+            PixelFormat::Rgb => (p[0], p[1], p[2], u8::MAX),
+            PixelFormat::Rgba => (p[0], p[1], p[2], p[3]),
+            PixelFormat::Bgr => (p[2], p[1], p[0], u8::MAX),
+            PixelFormat::Bgra => (p[2], p[1], p[0], p[3]),
+        };
+        Rgba { r, g, b, a }
+    }
+}
+
+/// A borrowed raw pixels buffer (e.g. a framebuffer, or a buffer from an FFI call).
+///
+/// ```
+/// # use aarty::{buffer::{PixelFormat, RawImage}, PixelImage, Rgba};
+/// // 2x2 RGB pixels, every row is padded to 8 bytes.
+/// let data = [
+///     255, 0, 0, 0, 255, 0, 0, 0,
+///     0, 0, 255, 9, 9, 9, 0, 0,
+/// ];
+/// let image = RawImage::with_stride(&data, 2, 2, 8, PixelFormat::Rgb).unwrap();
+/// assert_eq!(image.get_pixel(0, 1), Rgba { r: 0, g: 0, b: 255, a: 255 });
+/// assert_eq!(image.get_pixel(1, 1), Rgba { r: 9, g: 9, b: 9, a: 255 });
+///
+/// // The buffer is too short.
+/// assert!(RawImage::new(&data, 4, 4, PixelFormat::Rgba).is_none());
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct RawImage<'a> {
+    data: &'a [u8],
+    width: u32,
+    height: u32,
+    /// The bytes between the start of a row and the start of the next one.
+    stride: usize,
+    format: PixelFormat,
+}
+
+impl<'a> RawImage<'a> {
+    /// Construct a new instance from tightly packed rows.
+    ///
+    /// Return [`None`] if the buffer is smaller than the dimensions.
+    #[inline]
+    pub fn new(data: &'a [u8], width: u32, height: u32, format: PixelFormat) -> Option<Self> {
+        Self::with_stride(
+            data,
+            width,
+            height,
+            width as usize * format.bytes_per_pixel(),
+            format,
+        )
+    }
+
+    /// Construct a new instance from rows that are `stride` bytes apart (the rows may be padded).
+    ///
+    /// Return [`None`] if the stride is smaller than a row, or if the buffer is smaller than the dimensions.
+    pub fn with_stride(
+        data: &'a [u8],
+        width: u32,
+        height: u32,
+        stride: usize,
+        format: PixelFormat,
+    ) -> Option<Self> {
+        let row = width as usize * format.bytes_per_pixel();
+        if stride < row {
+            return None;
+        }
+        // The last row doesn't need the padding.
+        let len = match height as usize {
+            0 => 0,
+            h => (h - 1) * stride + row,
+        };
+        if data.len() < len {
+            return None;
+        }
+        Some(Self {
+            data,
+            width,
+            height,
+            stride,
+            format,
+        })
+    }
+
+    /// The pixels layout.
+    #[inline(always)]
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    /// The bytes between the start of a row and the start of the next one.
+    #[inline(always)]
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+}
+
+impl PixelImage for RawImage<'_> {
+    #[inline(always)]
+    fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    #[inline]
+    fn get_pixel(&self, x: u32, y: u32) -> Rgba {
+        let bpp = self.format.bytes_per_pixel();
+        let i = y as usize * self.stride + x as usize * bpp;
+        self.format.pixel(&self.data[i..i + bpp])
+    }
+}
+
+/// An owned grid of pixels, stored row by row.
+///
+/// ```
+/// # use aarty::{buffer::PixelGrid, PixelImage, Rgba};
+/// let black = Rgba { r: 0, g: 0, b: 0, a: 255 };
+/// let mut grid = PixelGrid::new(3, 2, black.clone());
+/// grid.put(2, 1, Rgba { r: 255, g: 255, b: 255, a: 255 });
+/// assert_eq!(grid.dimensions(), (3, 2));
+/// assert_eq!(grid.get_pixel(2, 1).r, 255);
+///
+/// // From existing pixels.
+/// let grid = PixelGrid::from_pixels(3, vec![black; 6]).unwrap();
+/// assert_eq!(grid.dimensions(), (3, 2));
+/// ```
+#[derive(Debug, PartialEq, PartialOrd, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PixelGrid {
+    pixels: Vec<Rgba>,
+    width: u32,
+}
+
+impl PixelGrid {
+    /// Construct a new grid filled with a pixel.
+    pub fn new(width: u32, height: u32, fill: Rgba) -> Self {
+        Self {
+            pixels: vec![fill; width as usize * height as usize],
+            width,
+        }
+    }
+
+    /// Construct a new grid from the pixels, row by row.
+    ///
+    /// Return [`None`] if the pixels can't be split into rows of `width` pixels.
+    pub fn from_pixels(width: u32, pixels: Vec<Rgba>) -> Option<Self> {
+        if width == 0 && !pixels.is_empty() || width != 0 && pixels.len() % width as usize != 0 {
+            return None;
+        }
+        Some(Self { pixels, width })
+    }
+
+    /// Set a pixel.
+    ///
+    /// # Panics
+    /// If the coordinates are out of the grid.
+    #[inline]
+    pub fn put(&mut self, x: u32, y: u32, pixel: Rgba) {
+        assert!(
+            x < self.width,
+            "{x} is out of the grid width {}",
+            self.width
+        );
+        self.pixels[y as usize * self.width as usize + x as usize] = pixel;
+    }
+
+    /// The pixels, row by row.
+    #[inline(always)]
+    pub fn as_pixels(&self) -> &[Rgba] {
+        &self.pixels
+    }
+
+    /// Return the pixels, row by row.
+    #[inline(always)]
+    pub fn into_pixels(self) -> Vec<Rgba> {
+        self.pixels
+    }
+}
+
+impl PixelImage for PixelGrid {
+    #[inline]
+    fn dimensions(&self) -> (u32, u32) {
+        if self.width == 0 {
+            return (0, 0);
+        }
+        (self.width, (self.pixels.len() / self.width as usize) as u32)
+    }
+
+    #[inline]
+    fn get_pixel(&self, x: u32, y: u32) -> Rgba {
+        self.pixels[y as usize * self.width as usize + x as usize].clone()
+    }
+}
+
+/// A procedural image, every pixel is calculated by a closure.
+///
+/// ```
+/// # use aarty::{buffer::FnPixelImage, fragments, Config, FragmentItem, Rgba, Sympols};
+/// // A diagonal line.
+/// let image = FnPixelImage::new(4, 4, |x, y| {
+///     let v = if x == y { 255 } else { 0 };
+///     Rgba { r: v, g: v, b: v, a: 255 }
+/// });
+/// let config = Config::new(Sympols::new(vec![' ', '\\']));
+/// let text: String = fragments(&config, &image)
+///     .map(|item| item.fragment().map_or('\n', |info| info.sym))
+///     .collect();
+/// assert_eq!(text, "\\   \n \\  \n  \\ \n   \\\n");
+/// ```
+#[derive(Clone, Copy)]
+pub struct FnPixelImage<F> {
+    width: u32,
+    height: u32,
+    f: F,
+}
+
+impl<F: Fn(u32, u32) -> Rgba> FnPixelImage<F> {
+    /// Construct a new instance, `f` gets the pixel coordinates and returns its value.
+    #[inline]
+    pub fn new(width: u32, height: u32, f: F) -> Self {
+        Self { width, height, f }
+    }
+}
+
+impl<F: Fn(u32, u32) -> Rgba> PixelImage for FnPixelImage<F> {
+    #[inline(always)]
+    fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    #[inline(always)]
+    fn get_pixel(&self, x: u32, y: u32) -> Rgba {
+        (self.f)(x, y)
+    }
+}
+
+impl<F> std::fmt::Debug for FnPixelImage<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnPixelImage")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
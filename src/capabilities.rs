@@ -0,0 +1,228 @@
+use std::{
+    collections::HashMap,
+    env,
+    io::{self, IsTerminal},
+    time::Duration,
+};
+
+use crate::{color::ColorDepth, graphics::Graphics, Config, COLORS};
+
+/// The default time to wait for the terminal to answer the queries.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// The kitty graphics protocol query, asks the terminal to validate a 1x1 RGB image without displaying it.
+const KITTY_QUERY: &[u8] = b"\x1b_Gi=31,s=1,v=1,a=q,t=d,f=24;AAAA\x1b\\";
+/// `XTGETTCAP` query for the `RGB` capability, the hex encoding of `RGB` is `524742`.
+const RGB_QUERY: &[u8] = b"\x1bP+q524742\x1b\\";
+/// The primary device attributes query, every terminal answer it, so its answer marks the end of the responses.
+const DA1_QUERY: &[u8] = b"\x1b[c";
+
+/// A source of environment variables.
+pub trait Environment {
+    /// Get the value of an environment variable.
+    fn var(&self, name: &str) -> Option<String>;
+}
+
+/// The process environment.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemEnvironment;
+
+impl Environment for SystemEnvironment {
+    #[inline]
+    fn var(&self, name: &str) -> Option<String> {
+        env::var(name).ok()
+    }
+}
+
+impl<K, V> Environment for HashMap<K, V>
+where
+    K: std::borrow::Borrow<str> + std::hash::Hash + Eq,
+    V: AsRef<str>,
+{
+    #[inline]
+    fn var(&self, name: &str) -> Option<String> {
+        self.get(name).map(|v| v.as_ref().to_string())
+    }
+}
+
+impl<const N: usize> Environment for [(&str, &str); N] {
+    #[inline]
+    fn var(&self, name: &str) -> Option<String> {
+        self.iter()
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v.to_string())
+    }
+}
+
+/// A terminal that can be asked about its capabilities with escape sequences.
+pub trait TerminalQuery {
+    /// Send the request to the terminal, and return what it answered within the timeout.
+    ///
+    /// The implementations should stop reading as soon as they got the answer for the primary device attributes query (`CSI c`),
+    /// which is always sent as the last query in the request.
+    fn query(&mut self, request: &[u8], timeout: Duration) -> io::Result<Vec<u8>>;
+}
+
+/// What the terminal can display.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// The color depth, [`None`] if the terminal can't (or the user don't want to) display colors.
+    pub colors: Option<ColorDepth>,
+    /// Support the sixel graphics.
+    pub sixel: bool,
+    /// Support the kitty graphics protocol.
+    pub kitty: bool,
+}
+
+impl Capabilities {
+    /// Detect the capabilities from the environment variables (`NO_COLOR`, `COLORTERM`, `TERM` and `TERM_PROGRAM`).
+    ///
+    /// ```
+    /// # use aarty::{capabilities::Capabilities, ColorDepth};
+    /// let caps = Capabilities::from_env(&[("TERM", "xterm-256color"), ("COLORTERM", "truecolor")]);
+    /// assert_eq!(caps.colors, Some(ColorDepth::TrueColor));
+    ///
+    /// let caps = Capabilities::from_env(&[("TERM", "xterm-kitty"), ("NO_COLOR", "1")]);
+    /// assert_eq!(caps.colors, None);
+    /// assert!(caps.kitty);
+    /// ```
+    pub fn from_env<E: Environment + ?Sized>(env: &E) -> Self {
+        let term = env.var("TERM").unwrap_or_default();
+        let program = env.var("TERM_PROGRAM").unwrap_or_default();
+        let colorterm = env.var("COLORTERM").unwrap_or_default();
+
+        let mut colors = if term == "dumb" {
+            None
+        } else if matches!(colorterm.as_str(), "truecolor" | "24bit")
+            || term.contains("truecolor")
+            || term.contains("direct")
+            || matches!(
+                program.as_str(),
+                "iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "Hyper"
+            )
+            || term == "xterm-kitty"
+            || term.starts_with("alacritty")
+            || term.starts_with("foot")
+        {
+            Some(ColorDepth::TrueColor)
+        } else if term.contains("256color") || program == "Apple_Terminal" {
+            Some(ColorDepth::Ansi256)
+        } else if !term.is_empty() || !colorterm.is_empty() {
+            Some(ColorDepth::Ansi16)
+        } else {
+            None
+        };
+
+        // https://no-color.org
+        if env.var("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            colors = None;
+        }
+
+        Capabilities {
+            colors,
+            sixel: term.starts_with("foot") || term.starts_with("mlterm") || program == "WezTerm",
+            kitty: term == "xterm-kitty" || matches!(program.as_str(), "WezTerm" | "ghostty"),
+        }
+    }
+
+    /// Detect the capabilities from the environment variables, then ask the terminal about what the environment can't tell.
+    ///
+    /// The terminal is asked about the kitty graphics protocol, the sixel graphics (in the device attributes) and the
+    /// truecolor support (with `XTGETTCAP`), if the terminal didn't answer (e.g. failed or timed out), the environment
+    /// detection result is returned.
+    ///
+    /// ```
+    /// # use aarty::{capabilities::*, ColorDepth};
+    /// # use std::{io, time::Duration};
+    /// /// A fake terminal that supports sixel and truecolor.
+    /// struct FakeTerminal;
+    ///
+    /// impl TerminalQuery for FakeTerminal {
+    ///     fn query(&mut self, _request: &[u8], _timeout: Duration) -> io::Result<Vec<u8>> {
+    ///         Ok(b"\x1bP1+r524742=382F382F38\x1b\\\x1b[?62;4;22c".to_vec())
+    ///     }
+    /// }
+    ///
+    /// let caps = Capabilities::probe(&[("TERM", "xterm")], &mut FakeTerminal, DEFAULT_TIMEOUT);
+    /// assert_eq!(caps.colors, Some(ColorDepth::TrueColor));
+    /// assert!(caps.sixel);
+    /// assert!(!caps.kitty);
+    /// ```
+    pub fn probe<E, Q>(env: &E, terminal: &mut Q, timeout: Duration) -> Self
+    where
+        E: Environment + ?Sized,
+        Q: TerminalQuery + ?Sized,
+    {
+        let mut caps = Self::from_env(env);
+
+        let request = [KITTY_QUERY, RGB_QUERY, DA1_QUERY].concat();
+        let Ok(response) = terminal.query(&request, timeout) else {
+            return caps;
+        };
+
+        caps.kitty |= contains(&response, b"\x1b_Gi=31;OK");
+        if let Some(attrs) = device_attributes(&response) {
+            caps.sixel |= attrs.contains(&4);
+        }
+        if caps.colors.is_some() && contains(&response, b"\x1bP1+r524742") {
+            caps.colors = Some(ColorDepth::TrueColor);
+        }
+
+        caps
+    }
+
+    /// Detect the capabilities of the terminal that the process is attached to.
+    ///
+    /// The terminal is only queried if the stdout is a terminal, See: [`Capabilities::probe`].
+    pub fn detect() -> Self {
+        #[cfg(unix)]
+        if io::stdout().is_terminal() {
+            if let Ok(mut tty) = crate::terminal::Tty::open() {
+                return Self::probe(&SystemEnvironment, &mut tty, DEFAULT_TIMEOUT);
+            }
+        }
+        Self::from_env(&SystemEnvironment)
+    }
+
+    /// The best graphics protocol that the terminal supports.
+    #[inline]
+    pub fn graphics(&self) -> Option<Graphics> {
+        if self.kitty {
+            Some(Graphics::Kitty)
+        } else if self.sixel {
+            Some(Graphics::Sixel)
+        } else {
+            None
+        }
+    }
+
+    /// Enable (or disable) the colors in the config and set the color depth to what the terminal supports.
+    #[must_use]
+    pub fn apply(&self, config: Config) -> Config {
+        match self.colors {
+            Some(depth) => {
+                let flags = config.flags | COLORS;
+                config.with_flags(flags).with_color_depth(depth)
+            }
+            None => {
+                let flags = config.flags & !COLORS;
+                config.with_flags(flags)
+            }
+        }
+    }
+}
+
+#[inline]
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+/// Parse the primary device attributes response (`CSI ? Ps ; ... c`).
+fn device_attributes(response: &[u8]) -> Option<Vec<u32>> {
+    let start = response.windows(3).position(|w| w == b"\x1b[?")? + 3;
+    let len = response[start..].iter().position(|b| *b == b'c')?;
+    let attrs = std::str::from_utf8(&response[start..start + len]).ok()?;
+    Some(attrs.split(';').filter_map(|a| a.parse().ok()).collect())
+}
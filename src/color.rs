@@ -1,65 +1,187 @@
-use std::fmt::Display;
+use std::{error::Error, fmt::Display, str::FromStr};
 
 use crate::Rgba;
 
+/// The CSS color names.
+mod names;
+
 pub(crate) const ANSI_ESCAPE_CLOSE: &str = "\u{001b}[0m";
 pub(crate) const ANSI_FOREGROUND_ESCAPE: &str = "\u{001b}[38;2;";
 pub(crate) const ANSI_BACKGROUND_ESCAPE: &str = "\u{001b}[48;2;";
-pub(crate) const ANSI_COLOR_CODE_LEN: usize = 12;
 pub(crate) const TRANSBARENT: ANSIColor = ANSIColor {
-    inner: String::new(),
+    inner: Inner::Transparent,
 };
 
+/// The standard 16 colors as most of the terminals (xterm) render them.
+const ANSI_16: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// The levels of the 6x6x6 color cube in the 256 colors palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// How many colors the terminal can display.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorDepth {
+    /// The 16 standard colors.
+    Ansi16,
+    /// The 256 colors palette.
+    Ansi256,
+    /// 24-bit RGB colors.
+    #[default]
+    TrueColor,
+}
+
 /// ansi color.
 #[derive(Debug, PartialEq, PartialOrd, Clone, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ANSIColor {
-    inner: String,
+    inner: Inner,
+}
+
+#[derive(Debug, PartialEq, PartialOrd, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Inner {
+    Transparent,
+    Rgb(u8, u8, u8),
+    Indexed(u8),
 }
 
 impl ANSIColor {
     /// Construct a new instance.
-    pub fn new(r: u8, g: u8, b: u8) -> ANSIColor {
-        let mut c = String::with_capacity(ANSI_COLOR_CODE_LEN);
-
-        for i in [r, g, b] {
-            c.push_str(&i.to_string());
-            c.push(';');
+    #[inline]
+    pub const fn new(r: u8, g: u8, b: u8) -> ANSIColor {
+        ANSIColor {
+            inner: Inner::Rgb(r, g, b),
         }
-        let _ = c.pop();
-
-        debug_assert_eq!(c.capacity(), ANSI_COLOR_CODE_LEN);
+    }
 
-        ANSIColor { inner: c }
+    /// Construct a new instance from the 256 colors palette index (the first 16 colors are the standard ANSI colors).
+    #[inline]
+    pub const fn indexed(idx: u8) -> ANSIColor {
+        ANSIColor {
+            inner: Inner::Indexed(idx),
+        }
     }
 
     /// return true if the color is transparent.
     #[inline(always)]
     pub fn is_transparent(&self) -> bool {
-        self.inner.is_empty()
+        self.inner == Inner::Transparent
+    }
+
+    /// Return the RGB value of the color, the palette colors are approximated to the xterm defaults.
+    ///
+    /// Return [`None`] if the color is transparent.
+    pub fn rgb(&self) -> Option<(u8, u8, u8)> {
+        match self.inner {
+            Inner::Transparent => None,
+            Inner::Rgb(r, g, b) => Some((r, g, b)),
+            Inner::Indexed(idx) => Some(palette_rgb(idx)),
+        }
+    }
+
+    /// Return the black or the white, whichever is more readable over this color (the transparent stays transparent).
+    ///
+    /// ```
+    /// # use aarty::ANSIColor;
+    /// assert_eq!(ANSIColor::new(250, 220, 10).contrast(), ANSIColor::new(0, 0, 0));
+    /// assert_eq!(ANSIColor::new(20, 20, 120).contrast(), ANSIColor::new(255, 255, 255));
+    /// ```
+    pub fn contrast(&self) -> ANSIColor {
+        let Some((r, g, b)) = self.rgb() else {
+            return TRANSBARENT;
+        };
+        let luma = 0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32;
+        if luma > 127.5 {
+            ANSIColor::new(0, 0, 0)
+        } else {
+            ANSIColor::new(255, 255, 255)
+        }
+    }
+
+    /// Return the palette index if it's a palette color.
+    #[inline]
+    pub fn index(&self) -> Option<u8> {
+        match self.inner {
+            Inner::Indexed(idx) => Some(idx),
+            _ => None,
+        }
+    }
+
+    /// Convert the color to the nearest color that can be displayed with the given depth.
+    ///
+    /// ```
+    /// # use aarty::{ANSIColor, color::ColorDepth};
+    /// assert_eq!(ANSIColor::new(250, 10, 5).with_depth(ColorDepth::Ansi256), ANSIColor::indexed(196));
+    /// assert_eq!(ANSIColor::new(250, 10, 5).with_depth(ColorDepth::Ansi16), ANSIColor::indexed(9));
+    /// assert_eq!(ANSIColor::new(250, 10, 5).with_depth(ColorDepth::TrueColor), ANSIColor::new(250, 10, 5));
+    /// ```
+    pub fn with_depth(&self, depth: ColorDepth) -> ANSIColor {
+        let Some((r, g, b)) = self.rgb() else {
+            return TRANSBARENT;
+        };
+        match (depth, &self.inner) {
+            (ColorDepth::TrueColor, _) => self.clone(),
+            (ColorDepth::Ansi256, Inner::Indexed(_)) => self.clone(),
+            (ColorDepth::Ansi16, Inner::Indexed(idx)) if *idx < 16 => self.clone(),
+            (ColorDepth::Ansi256, _) => ANSIColor::indexed(nearest_256(r, g, b)),
+            (ColorDepth::Ansi16, _) => {
+                ANSIColor::indexed(nearest(r, g, b, (0..16).map(|i| (i, ANSI_16[i as usize]))))
+            }
+        }
     }
 
     /// Return an ANSI escaped background color.
     pub fn as_background(&self) -> String {
-        format!("{ANSI_BACKGROUND_ESCAPE}{}m", self.inner)
+        format!("{self:-}")
     }
 
     /// Return an ANSI escaped foreground color.
     pub fn as_foreground(&self) -> String {
-        format!("{ANSI_FOREGROUND_ESCAPE}{}m", self.inner)
+        format!("{self}")
     }
 }
 
 impl Display for ANSIColor {
+    /// Write the foreground escape code, or the background one with the `-` flag (`{color:-}`).
     #[inline]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.is_transparent() {
-            return Ok(());
-        }
-        if f.sign_minus() {
-            write!(f, "{ANSI_BACKGROUND_ESCAPE}{}m", self.inner)
-        } else {
-            write!(f, "{ANSI_FOREGROUND_ESCAPE}{}m", self.inner)
+        let background = f.sign_minus();
+        match self.inner {
+            Inner::Transparent => Ok(()),
+            Inner::Rgb(r, g, b) => {
+                let escape = if background {
+                    ANSI_BACKGROUND_ESCAPE
+                } else {
+                    ANSI_FOREGROUND_ESCAPE
+                };
+                write!(f, "{escape}{r};{g};{b}m")
+            }
+            Inner::Indexed(idx) => {
+                let base = if background { 40 } else { 30 };
+                match idx {
+                    0..=7 => write!(f, "\u{001b}[{}m", base + idx),
+                    8..=15 => write!(f, "\u{001b}[{}m", base + 60 + idx - 8),
+                    _ => write!(f, "\u{001b}[{};5;{idx}m", base + 8),
+                }
+            }
         }
     }
 }
@@ -73,13 +195,258 @@ where
     }
 }
 
+/// A fully transparent pixel is the transparent color, the alpha of the others is ignored
+/// (they're composited before, see: [`crate::alpha::Alpha`]).
 impl From<Rgba> for ANSIColor {
     #[inline(always)]
     fn from(value: Rgba) -> Self {
         let Rgba { r, g, b, a } = value;
-        if a < 120 {
+        if a == 0 {
             return TRANSBARENT;
         }
         ANSIColor::new(r, g, b)
     }
 }
+
+impl FromStr for ANSIColor {
+    type Err = ParseColorError;
+
+    /// Parse a color from `#rgb`, `#rrggbb`, `rgb(r, g, b)`, `hsl(h, s%, l%)`, a CSS color name
+    /// or a 256 colors palette index.
+    ///
+    /// ```
+    /// # use aarty::ANSIColor;
+    /// assert_eq!("#ffd0bb".parse(), Ok(ANSIColor::new(255, 208, 187)));
+    /// assert_eq!("#fdb".parse(), Ok(ANSIColor::new(255, 221, 187)));
+    /// assert_eq!("rgb(255, 208, 187)".parse(), Ok(ANSIColor::new(255, 208, 187)));
+    /// assert_eq!("rgb(100% 0% 50%)".parse(), Ok(ANSIColor::new(255, 0, 128)));
+    /// assert_eq!("hsl(120, 100%, 25%)".parse(), Ok(ANSIColor::new(0, 128, 0)));
+    /// assert_eq!("Rebecca Purple".parse(), Ok(ANSIColor::new(102, 51, 153)));
+    /// assert_eq!("grey50".parse(), Ok(ANSIColor::new(128, 128, 128)));
+    /// assert_eq!("208".parse(), Ok(ANSIColor::indexed(208)));
+    ///
+    /// assert!("#ffd0b".parse::<ANSIColor>().is_err());
+    /// assert!("rgb(256, 0, 0)".parse::<ANSIColor>().is_err());
+    /// assert!("256".parse::<ANSIColor>().is_err());
+    /// assert!("not a color".parse::<ANSIColor>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseColorError::Empty);
+        }
+
+        if let Some(hex) = s.strip_prefix('#') {
+            return parse_hex(hex)
+                .map(|(r, g, b)| ANSIColor::new(r, g, b))
+                .ok_or_else(|| ParseColorError::InvalidHex(s.to_string()));
+        }
+
+        if s.bytes().all(|b| b.is_ascii_digit()) {
+            return s
+                .parse::<u8>()
+                .map(ANSIColor::indexed)
+                .map_err(|_| ParseColorError::OutOfRange(s.to_string()));
+        }
+
+        let lower = s.to_ascii_lowercase();
+        if let Some((func, args)) = lower
+            .strip_suffix(')')
+            .and_then(|rest| rest.split_once('('))
+        {
+            return parse_function(s, func.trim(), args).map(|(r, g, b)| ANSIColor::new(r, g, b));
+        }
+
+        parse_name(&lower)
+            .map(|(r, g, b)| ANSIColor::new(r, g, b))
+            .ok_or_else(|| ParseColorError::UnknownName(s.to_string()))
+    }
+}
+
+/// An error which can be returned when parsing a color.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ParseColorError {
+    /// The input is empty.
+    Empty,
+    /// Invalid hex color, it should be `#rgb` or `#rrggbb`.
+    InvalidHex(String),
+    /// Invalid `rgb(...)` or `hsl(...)` color, or an unknown function.
+    InvalidFunction(String),
+    /// A value that's out of its range, like a palette index greater than 255.
+    OutOfRange(String),
+    /// Unknown color name.
+    UnknownName(String),
+}
+
+impl Display for ParseColorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseColorError::Empty => f.write_str("empty color"),
+            ParseColorError::InvalidHex(s) => {
+                write!(f, "invalid hex color \"{s}\", expected #rgb or #rrggbb")
+            }
+            ParseColorError::InvalidFunction(s) => write!(
+                f,
+                "invalid color \"{s}\", expected rgb(r, g, b) or hsl(h, s%, l%)"
+            ),
+            ParseColorError::OutOfRange(s) => write!(
+                f,
+                "the color \"{s}\" is out of range, the rgb values are 0-255 (or 0%-100%) and the palette indices are 0-255"
+            ),
+            ParseColorError::UnknownName(s) => write!(
+                f,
+                "unknown color \"{s}\", expected a CSS color name, #rgb, #rrggbb, rgb(r, g, b), hsl(h, s%, l%) or a palette index (0-255)"
+            ),
+        }
+    }
+}
+
+impl Error for ParseColorError {}
+
+fn parse_hex(hex: &str) -> Option<(u8, u8, u8)> {
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..=i], 16).ok();
+    let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    match hex.len() {
+        3 => Some((digit(0)? * 17, digit(1)? * 17, digit(2)? * 17)),
+        6 => Some((pair(0)?, pair(2)?, pair(4)?)),
+        _ => None,
+    }
+}
+
+/// Parse the `rgb(...)` and `hsl(...)` arguments, `input` is the whole color (used in the errors).
+fn parse_function(input: &str, func: &str, args: &str) -> Result<(u8, u8, u8), ParseColorError> {
+    let invalid = || ParseColorError::InvalidFunction(input.to_string());
+    let out_of_range = || ParseColorError::OutOfRange(input.to_string());
+    let number = |v: &str| {
+        v.parse::<f32>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .ok_or_else(invalid)
+    };
+
+    let args: Vec<&str> = args
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|a| !a.is_empty())
+        .collect();
+    let [a, b, c] = args[..] else {
+        return Err(invalid());
+    };
+
+    match func {
+        "rgb" => {
+            let channel = |v: &str| {
+                let (v, max) = match v.strip_suffix('%') {
+                    Some(v) => (number(v)?, 100.),
+                    None => (number(v)?, 255.),
+                };
+                if !(0. ..=max).contains(&v) {
+                    return Err(out_of_range());
+                }
+                Ok((v * 255. / max).round() as u8)
+            };
+            Ok((channel(a)?, channel(b)?, channel(c)?))
+        }
+        "hsl" => {
+            let hue = number(a.strip_suffix("deg").unwrap_or(a))?;
+            let percent = |v: &str| {
+                let v = number(v.strip_suffix('%').unwrap_or(v))?;
+                if !(0. ..=100.).contains(&v) {
+                    return Err(out_of_range());
+                }
+                Ok(v / 100.)
+            };
+            Ok(hsl_to_rgb(hue.rem_euclid(360.), percent(b)?, percent(c)?))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (u8, u8, u8) {
+    let c = (1. - (2. * l - 1.).abs()) * s;
+    let x = c * (1. - ((h / 60.) % 2. - 1.).abs());
+    let m = l - c / 2.;
+    let (r, g, b) = match h as u32 / 60 {
+        0 => (c, x, 0.),
+        1 => (x, c, 0.),
+        2 => (0., c, x),
+        3 => (0., x, c),
+        4 => (x, 0., c),
+        _ => (c, 0., x),
+    };
+    let channel = |v: f32| ((v + m) * 255.).round() as u8;
+    (channel(r), channel(g), channel(b))
+}
+
+/// Look up a CSS color name, the spaces and the underscores are ignored (`rebecca purple`, `navy_blue`),
+/// and a few X11 extras (e.g. `lightgoldenrod`, `navyblue` and the `grayN`/`greyN` levels from 0 to 100) are supported.
+fn parse_name(name: &str) -> Option<(u8, u8, u8)> {
+    let name: String = name.chars().filter(|c| !matches!(c, ' ' | '_')).collect();
+    if let Ok(i) = names::NAMES.binary_search_by_key(&name.as_str(), |(n, _)| n) {
+        return Some(names::NAMES[i].1);
+    }
+    let level: u8 = name
+        .strip_prefix("gray")
+        .or_else(|| name.strip_prefix("grey"))?
+        .parse()
+        .ok()
+        .filter(|l| *l <= 100)?;
+    let v = (level as f32 * 2.55).round() as u8;
+    Some((v, v, v))
+}
+
+/// The RGB value of a color in the xterm 256 colors palette.
+fn palette_rgb(idx: u8) -> (u8, u8, u8) {
+    match idx {
+        0..=15 => ANSI_16[idx as usize],
+        16..=231 => {
+            let i = idx - 16;
+            (
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[(i / 6 % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let v = 8 + (idx - 232) * 10;
+            (v, v, v)
+        }
+    }
+}
+
+/// Find the nearest color in the 6x6x6 cube and the grayscale ramp of the 256 colors palette.
+fn nearest_256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |v: u8| {
+        CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, l)| (**l as i32 - v as i32).abs())
+            .map_or(0, |(i, _)| i as u8)
+    };
+    let cube = 16 + 36 * level(r) + 6 * level(g) + level(b);
+    let avg = (r as u32 + g as u32 + b as u32) / 3;
+    let gray = 232 + (avg.saturating_sub(3) / 10).min(23) as u8;
+    nearest(r, g, b, [cube, gray].map(|i| (i, palette_rgb(i))))
+}
+
+#[inline]
+fn nearest<I>(r: u8, g: u8, b: u8, colors: I) -> u8
+where
+    I: IntoIterator<Item = (u8, (u8, u8, u8))>,
+{
+    let dist = |(cr, cg, cb): (u8, u8, u8)| {
+        let (dr, dg, db) = (
+            cr as i32 - r as i32,
+            cg as i32 - g as i32,
+            cb as i32 - b as i32,
+        );
+        dr * dr + dg * dg + db * db
+    };
+    colors
+        .into_iter()
+        .min_by_key(|(_, c)| dist(*c))
+        .map_or(0, |(i, _)| i)
+}
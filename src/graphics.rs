@@ -0,0 +1,240 @@
+use std::{
+    fmt::Display,
+    io::{self, Write},
+    str::FromStr,
+};
+
+use crate::PixelImage;
+
+/// The max payload size in a single kitty graphics protocol escape code.
+const KITTY_CHUNK: usize = 4096;
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+/// The sixel palette is a 6x6x6 color cube.
+const SIXEL_LEVELS: u8 = 6;
+
+/// Terminal graphics protocols, they display the image pixels instead of characters.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Graphics {
+    /// The [kitty graphics protocol](https://sw.kovidgoyal.net/kitty/graphics-protocol/).
+    Kitty,
+    /// DEC sixel graphics.
+    Sixel,
+}
+
+impl Graphics {
+    /// Write the image to the terminal with this protocol.
+    ///
+    /// `cells` is the area (in columns and rows) that the image should be scaled to fill, it's only supported by
+    /// [`Graphics::Kitty`], with sixel the image is displayed in its pixel size.
+    ///
+    /// ```
+    /// # use aarty::{buffer::FnPixelImage, graphics::Graphics, Rgba};
+    /// let red = FnPixelImage::new(1, 1, |_, _| Rgba { r: 255, g: 0, b: 0, a: 255 });
+    /// let mut out = Vec::new();
+    /// Graphics::Kitty.write(&red, &mut out, Some((2, 1))).unwrap();
+    /// // The RGBA pixels in base64.
+    /// assert_eq!(out, b"\x1b_Ga=T,f=32,s=1,v=1,c=2,r=1,m=0;/wAA/w==\x1b\\");
+    ///
+    /// // The big images are sent in 4096 bytes chunks.
+    /// let gray = FnPixelImage::new(64, 64, |_, _| Rgba { r: 9, g: 9, b: 9, a: 255 });
+    /// let mut out = Vec::new();
+    /// Graphics::Kitty.write(&gray, &mut out, None).unwrap();
+    /// let out = String::from_utf8(out).unwrap();
+    /// let chunks: Vec<_> = out.split_terminator("\x1b\\").collect();
+    /// assert_eq!(chunks.len(), 6);
+    /// assert!(chunks[0].starts_with("\x1b_Ga=T,f=32,s=64,v=64,m=1;"));
+    /// assert!(chunks[1..5].iter().all(|c| c.starts_with("\x1b_Gm=1;") && c.len() == 4096 + 7));
+    /// assert!(chunks[5].starts_with("\x1b_Gm=0;"));
+    ///
+    /// // A red and a blue pixel in sixels, each color is drawn in its own pass over the band.
+    /// let image = FnPixelImage::new(2, 1, |x, _| match x {
+    ///     0 => Rgba { r: 255, g: 0, b: 0, a: 255 },
+    ///     _ => Rgba { r: 0, g: 0, b: 255, a: 255 },
+    /// });
+    /// let mut out = Vec::new();
+    /// Graphics::Sixel.write(&image, &mut out, None).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(out).unwrap(),
+    ///     "\x1bP0;1q\"1;1;2;1#5;2;0;0;100#180;2;100;0;0#5?@$#180@?-\x1b\\"
+    /// );
+    /// ```
+    pub fn write<I, W>(self, image: &I, out: &mut W, cells: Option<(u32, u32)>) -> io::Result<()>
+    where
+        I: PixelImage,
+        W: Write,
+    {
+        match self {
+            Graphics::Kitty => write_kitty(image, out, cells),
+            Graphics::Sixel => write_sixel(image, out),
+        }
+    }
+}
+
+impl FromStr for Graphics {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "kitty" => Ok(Graphics::Kitty),
+            "sixel" => Ok(Graphics::Sixel),
+            other => Err(format!(
+                "unknown graphics protocol `{other}`, expected kitty or sixel"
+            )),
+        }
+    }
+}
+
+impl Display for Graphics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Graphics::Kitty => "kitty",
+            Graphics::Sixel => "sixel",
+        })
+    }
+}
+
+fn write_kitty<I, W>(image: &I, out: &mut W, cells: Option<(u32, u32)>) -> io::Result<()>
+where
+    I: PixelImage,
+    W: Write,
+{
+    let (w, h) = image.dimensions();
+    let mut rgba = Vec::with_capacity(w as usize * h as usize * 4);
+    for y in 0..h {
+        for x in 0..w {
+            let p = image.get_pixel(x, y);
+            rgba.extend_from_slice(&[p.r, p.g, p.b, p.a]);
+        }
+    }
+    let payload = base64(&rgba);
+
+    let mut chunks = payload.chunks(KITTY_CHUNK).peekable();
+    let mut first = true;
+    while let Some(chunk) = chunks.next() {
+        let more = u8::from(chunks.peek().is_some());
+        if first {
+            write!(out, "\u{001b}_Ga=T,f=32,s={w},v={h}")?;
+            if let Some((c, r)) = cells {
+                write!(out, ",c={c},r={r}")?;
+            }
+            write!(out, ",m={more};")?;
+            first = false;
+        } else {
+            write!(out, "\u{001b}_Gm={more};")?;
+        }
+        out.write_all(chunk)?;
+        out.write_all(b"\x1b\\")?;
+    }
+    Ok(())
+}
+
+fn write_sixel<I, W>(image: &I, out: &mut W) -> io::Result<()>
+where
+    I: PixelImage,
+    W: Write,
+{
+    let (w, h) = image.dimensions();
+    let (wu, hu) = (w as usize, h as usize);
+
+    // The palette index of every pixel, `None` for the transparent ones.
+    let mut indices = Vec::with_capacity(wu * hu);
+    let mut used = [false; (SIXEL_LEVELS as usize).pow(3)];
+    for y in 0..h {
+        for x in 0..w {
+            let p = image.get_pixel(x, y);
+            if p.a < 128 {
+                indices.push(None);
+                continue;
+            }
+            let level = |v: u8| (v as u16 * (SIXEL_LEVELS as u16 - 1) + 127) / 255;
+            let idx = (level(p.r) * 36 + level(p.g) * 6 + level(p.b)) as usize;
+            used[idx] = true;
+            indices.push(Some(idx));
+        }
+    }
+
+    // Transparent background, and the image size.
+    write!(out, "\u{001b}P0;1q\"1;1;{w};{h}")?;
+    for (idx, _) in used.iter().enumerate().filter(|(_, used)| **used) {
+        let percent = |l: usize| l * 100 / (SIXEL_LEVELS as usize - 1);
+        write!(
+            out,
+            "#{idx};2;{};{};{}",
+            percent(idx / 36),
+            percent(idx / 6 % 6),
+            percent(idx % 6)
+        )?;
+    }
+
+    let mut row = vec![0u8; wu];
+    for band in (0..hu).step_by(6) {
+        let band_height = (hu - band).min(6);
+        let mut colors: Vec<usize> = (band..band + band_height)
+            .flat_map(|y| indices[y * wu..(y + 1) * wu].iter().flatten().copied())
+            .collect();
+        colors.sort_unstable();
+        colors.dedup();
+
+        for (i, color) in colors.iter().enumerate() {
+            if i > 0 {
+                // Back to the start of the band.
+                out.write_all(b"$")?;
+            }
+            for (x, sixel) in row.iter_mut().enumerate() {
+                *sixel = (0..band_height)
+                    .filter(|dy| indices[(band + dy) * wu + x] == Some(*color))
+                    .fold(0, |bits, dy| bits | (1 << dy));
+            }
+            write!(out, "#{color}")?;
+            write_sixel_row(&row, out)?;
+        }
+        out.write_all(b"-")?;
+    }
+
+    out.write_all(b"\x1b\\")
+}
+
+/// Write a row of sixels with run length encoding.
+fn write_sixel_row<W: Write>(row: &[u8], out: &mut W) -> io::Result<()> {
+    let mut i = 0;
+    while i < row.len() {
+        let run = row[i..].iter().take_while(|b| **b == row[i]).count();
+        let ch = (row[i] + 63) as char;
+        if run > 3 {
+            write!(out, "!{run}{ch}")?;
+        } else {
+            for _ in 0..run {
+                write!(out, "{ch}")?;
+            }
+        }
+        i += run;
+    }
+    Ok(())
+}
+
+fn base64(data: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        res.push(BASE64[(n >> 18) as usize & 63]);
+        res.push(BASE64[(n >> 12) as usize & 63]);
+        res.push(if chunk.len() > 1 {
+            BASE64[(n >> 6) as usize & 63]
+        } else {
+            b'='
+        });
+        res.push(if chunk.len() > 2 {
+            BASE64[n as usize & 63]
+        } else {
+            b'='
+        });
+    }
+    res
+}
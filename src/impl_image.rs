@@ -45,3 +45,116 @@ impl From<image::Rgba<u8>> for crate::ANSIColor {
         crate::ANSIColor::new(value[0], value[1], value[2])
     }
 }
+
+/// The grid pixels into an `image` crate buffer.
+///
+/// ```
+/// # use aarty::{buffer::PixelGrid, Rgba};
+/// # use image::RgbaImage;
+/// let grid = PixelGrid::new(3, 2, Rgba { r: 1, g: 2, b: 3, a: 4 });
+/// let image = RgbaImage::from(grid);
+/// assert_eq!(image.dimensions(), (3, 2));
+/// assert_eq!(image.get_pixel(2, 1).0, [1, 2, 3, 4]);
+/// ```
+impl From<crate::buffer::PixelGrid> for image::RgbaImage {
+    fn from(grid: crate::buffer::PixelGrid) -> Self {
+        let (width, height) = crate::PixelImage::dimensions(&grid);
+        let bytes = grid
+            .into_pixels()
+            .into_iter()
+            .flat_map(|p| [p.r, p.g, p.b, p.a])
+            .collect();
+        image::RgbaImage::from_raw(width, height, bytes).expect("the grid is exactly the size")
+    }
+}
+
+/// Convert an image with a decoder that can read rectangles (e.g. BMP, farbfeld), a strip of rows at a time,
+/// so the whole image is never decoded into the memory. See: [`crate::streaming::RowConverter`].
+///
+/// ```
+/// # use aarty::{impl_image::convert_decoder_rows, Config, Sympols};
+/// # use image::{codecs::bmp::BmpDecoder, GrayImage, ImageFormat, Luma};
+/// # use std::io::Cursor;
+/// let image = GrayImage::from_fn(64, 32, |x, _| Luma([if x < 32 { 0 } else { 255 }]));
+/// let mut bmp = Cursor::new(Vec::new());
+/// image.write_to(&mut bmp, ImageFormat::Bmp).unwrap();
+/// bmp.set_position(0);
+///
+/// let config = Config::new(Sympols::new(vec![' ', '#']));
+/// let decoder = BmpDecoder::new(bmp).unwrap();
+/// let out = convert_decoder_rows(decoder, &config, (4, 2), Vec::new()).unwrap();
+/// assert_eq!(String::from_utf8(out).unwrap(), "  ##\n  ##\n");
+/// ```
+pub fn convert_decoder_rows<D, W>(
+    mut decoder: D,
+    config: &crate::Config,
+    target: (u32, u32),
+    out: W,
+) -> Result<W, Box<dyn std::error::Error>>
+where
+    D: image::ImageDecoderRect,
+    W: crate::FragmentWriter,
+{
+    /// The max size of a decoded strip.
+    const STRIP_BYTES: usize = 4 * 1048576; // 4mb
+
+    let (width, height) = decoder.dimensions();
+    let color = decoder.color_type();
+    let row_bytes = width as usize * color.bytes_per_pixel() as usize;
+    let strip_rows = (STRIP_BYTES / row_bytes.max(1)).clamp(1, height.max(1) as usize) as u32;
+
+    let mut rows = crate::streaming::RowConverter::new(config, (width, height), target, out)?;
+    let mut buf = vec![0; strip_rows as usize * row_bytes];
+    let mut pixels = Vec::with_capacity(strip_rows as usize * width as usize);
+    let mut y = 0;
+    while y < height {
+        let strip = strip_rows.min(height - y);
+        let buf = &mut buf[..strip as usize * row_bytes];
+        decoder.read_rect(0, y, width, strip, buf, row_bytes)?;
+        pixels.clear();
+        decode_pixels(color, buf, &mut pixels)?;
+        rows.push_rows(&pixels)?;
+        y += strip;
+    }
+    rows.finish()
+}
+
+/// Convert the raw decoded bytes into pixels, the 16-bit channels are in the native endianness.
+fn decode_pixels(
+    color: image::ColorType,
+    bytes: &[u8],
+    out: &mut Vec<Rgba>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let channels = color.channel_count() as usize;
+    let channel_bytes = color.bytes_per_pixel() as usize / channels;
+    if channel_bytes > 2 {
+        return Err(format!("the {color:?} color type isn't supported").into());
+    }
+    let channel = |c: &[u8]| match c {
+        [v] => *v,
+        [a, b] => (u16::from_ne_bytes([*a, *b]) >> 8) as u8,
+        _ => unreachable!(),
+    };
+    for pixel in bytes.chunks_exact(channels * channel_bytes) {
+        let mut values = pixel.chunks_exact(channel_bytes).map(channel);
+        let mut next = || values.next().unwrap_or(u8::MAX);
+        out.push(match channels {
+            1 | 2 => {
+                let (v, a) = (next(), next());
+                Rgba {
+                    r: v,
+                    g: v,
+                    b: v,
+                    a,
+                }
+            }
+            _ => Rgba {
+                r: next(),
+                g: next(),
+                b: next(),
+                a: next(),
+            },
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use crate::{
+    levels::{self, ToneMap},
+    Config, FragmentInfo, PixelImage,
+};
+
+/// An item of the [`Fragments`] iterator.
+#[derive(Debug, PartialEq, PartialOrd, Clone, Hash)]
+#[non_exhaustive]
+pub enum FragmentItem {
+    /// The fragment of the pixel at `x` and `y`.
+    Fragment {
+        /// The column.
+        x: u32,
+        /// The row.
+        y: u32,
+        /// The fragment.
+        info: FragmentInfo,
+    },
+    /// The row `y` is finished.
+    RowEnd {
+        /// The row.
+        y: u32,
+    },
+}
+
+impl FragmentItem {
+    /// Return the fragment, or [`None`] if it's a row end.
+    #[inline]
+    pub fn fragment(self) -> Option<FragmentInfo> {
+        match self {
+            FragmentItem::Fragment { info, .. } => Some(info),
+            FragmentItem::RowEnd { .. } => None,
+        }
+    }
+}
+
+/// Iterate the image fragments lazily, row by row, each row is followed by a [`FragmentItem::RowEnd`].
+///
+/// The fragments are the same that [`crate::convert_image_to_ascii`] writes, without the background and the reverse mode
+/// (they're applied when the fragments are written).
+///
+/// With the adaptive [`crate::levels::Levels`], the whole image is read once when the iterator is constructed.
+/// With the [`crate::CELL_COLORS`] mode, the symbols are picked by the local contrast (and the levels are ignored).
+///
+/// ```
+/// # use aarty::{fragments, Config, FragmentItem, PixelImage, Rgba, Sympols};
+/// struct Gradient;
+///
+/// impl PixelImage for Gradient {
+///     fn dimensions(&self) -> (u32, u32) {
+///         (3, 2)
+///     }
+///
+///     fn get_pixel(&self, x: u32, _: u32) -> Rgba {
+///         let v = x as u8 * 127;
+///         Rgba { r: v, g: v, b: v, a: 255 }
+///     }
+/// }
+///
+/// let config = Config::new(Sympols::new(vec![' ', '.', '#']));
+/// let rows: Vec<String> = fragments(&config, &Gradient)
+///     .fold(vec![String::new()], |mut rows, item| {
+///         match item {
+///             FragmentItem::Fragment { info, .. } => rows.last_mut().unwrap().push(info.sym),
+///             FragmentItem::RowEnd { .. } => rows.push(String::new()),
+///             _ => {}
+///         }
+///         rows
+///     });
+/// assert_eq!(rows, [" .#", " .#", ""]);
+///
+/// // Only the symbols indices.
+/// let indices: Vec<_> = fragments(&config, &Gradient)
+///     .filter_map(FragmentItem::fragment)
+///     .map(|info| info.sym_index)
+///     .collect();
+/// assert_eq!(indices, [0, 1, 2, 0, 1, 2]);
+/// ```
+#[inline]
+pub fn fragments<'a, I: PixelImage>(config: &'a Config, image: &'a I) -> Fragments<'a, I> {
+    #[cfg(feature = "colors")]
+    let detail = config.cell_colors();
+    #[cfg(not(feature = "colors"))]
+    let detail = false;
+    Fragments {
+        config,
+        image,
+        size: image.dimensions(),
+        detail,
+        tone: (!detail).then(|| ToneMap::new(config, image)).flatten(),
+        x: 0,
+        y: 0,
+    }
+}
+
+/// The lazy fragments iterator, See: [`fragments`].
+#[derive(Debug)]
+pub struct Fragments<'a, I> {
+    config: &'a Config,
+    image: &'a I,
+    size: (u32, u32),
+    /// Pick the symbols by the local contrast.
+    detail: bool,
+    tone: Option<ToneMap>,
+    x: u32,
+    y: u32,
+}
+
+impl<I: PixelImage> Iterator for Fragments<'_, I> {
+    type Item = FragmentItem;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (width, height) = self.size;
+        if self.y >= height {
+            return None;
+        }
+        if self.x == width {
+            let y = self.y;
+            self.x = 0;
+            self.y += 1;
+            return Some(FragmentItem::RowEnd { y });
+        }
+        let (x, y) = (self.x, self.y);
+        self.x += 1;
+        Some(FragmentItem::Fragment {
+            x,
+            y,
+            info: FragmentInfo::mapped(self.config, x, y, self.image.get_pixel(x, y), |v| {
+                if self.detail {
+                    return levels::detail(self.config, self.image, x, y, v);
+                }
+                self.tone.as_ref().map_or(v, |tone| tone.map(x, y, v))
+            }),
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (width, height) = self.size;
+        let total = (width as usize + 1) * height as usize;
+        let done = (width as usize + 1) * self.y as usize + self.x as usize;
+        let remaining = total.saturating_sub(done);
+        (remaining, Some(remaining))
+    }
+}
+
+impl<I: PixelImage> ExactSizeIterator for Fragments<'_, I> {}
+
+impl<I: PixelImage> std::iter::FusedIterator for Fragments<'_, I> {}
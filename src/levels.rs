@@ -0,0 +1,300 @@
+use std::{
+    fmt::Display,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+use crate::{Config, PixelImage, Rgba};
+
+/// How the pixels brightness is mapped to the symbols.
+///
+/// The adaptive modes compute the image brightness histogram in a first pass, so a low-contrast image uses
+/// the whole symbols set. Only the symbols are affected, the colors are kept as they are.
+///
+/// ```
+/// # use aarty::{fragments, levels::Levels, Config, FragmentItem, PixelImage, Rgba, Sympols};
+/// /// A dim gradient, the brightness goes from 70 to 100.
+/// struct Dim;
+///
+/// impl PixelImage for Dim {
+///     fn dimensions(&self) -> (u32, u32) {
+///         (4, 1)
+///     }
+///
+///     fn get_pixel(&self, x: u32, _: u32) -> Rgba {
+///         let v = 70 + x as u8 * 10;
+///         Rgba { r: v, g: v, b: v, a: 255 }
+///     }
+/// }
+///
+/// let symbols = |config: &Config| -> String {
+///     fragments(config, &Dim).filter_map(FragmentItem::fragment).map(|info| info.sym).collect()
+/// };
+///
+/// let config = Config::new(Sympols::new(vec![' ', '.', ':', '#']));
+/// assert_eq!(symbols(&config), "....");
+/// assert_eq!(symbols(&config.with_levels(Levels::Equalize)), " .:#");
+///
+/// assert_eq!("clahe:4:3".parse(), Ok(Levels::Clahe { tiles: 4, clip_limit: 3. }));
+/// assert!("clahe:100000".parse::<Levels>().is_err());
+/// ```
+#[derive(Debug, Default, PartialEq, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Levels {
+    /// The absolute brightness, the default.
+    #[default]
+    Absolute,
+    /// The global histogram equalisation, every symbol gets (about) the same share of the pixels.
+    Equalize,
+    /// The contrast limited adaptive histogram equalisation, every region of the image is equalised on its own
+    /// (and blended with the neighbour regions), so the details in the dark and the bright areas are kept.
+    Clahe {
+        /// The regions count, on each axis (up to [`Levels::MAX_TILES`]), fewer are used if a region would be
+        /// smaller than 8 pixels.
+        tiles: u32,
+        /// How much a region contrast can be stretched, 1 keeps the image as it is (usually between 2 and 4).
+        clip_limit: f32,
+    },
+}
+
+impl Levels {
+    /// The CLAHE with the common parameters (8x8 regions and a 2.5 clip limit).
+    pub const CLAHE: Self = Self::Clahe {
+        tiles: 8,
+        clip_limit: 2.5,
+    };
+
+    /// The max CLAHE regions count on each axis.
+    pub const MAX_TILES: u32 = 64;
+}
+
+impl Hash for Levels {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        if let Levels::Clahe { tiles, clip_limit } = self {
+            tiles.hash(state);
+            clip_limit.to_bits().hash(state);
+        }
+    }
+}
+
+impl FromStr for Levels {
+    type Err = String;
+
+    /// Parse `absolute`, `equalize`, or `clahe` with optional tiles and clip limit (e.g. `clahe:8:2.5`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        match parts.next().unwrap_or_default() {
+            "absolute" | "none" => Ok(Levels::Absolute),
+            "equalize" | "equalise" => Ok(Levels::Equalize),
+            "clahe" => {
+                let Levels::Clahe {
+                    mut tiles,
+                    mut clip_limit,
+                } = Levels::CLAHE
+                else {
+                    unreachable!()
+                };
+                if let Some(t) = parts.next() {
+                    tiles = t
+                        .parse()
+                        .ok()
+                        .filter(|t| (1..=Levels::MAX_TILES).contains(t))
+                        .ok_or_else(|| {
+                            format!(
+                                "invalid CLAHE tiles count `{t}`, expected 1 to {}",
+                                Levels::MAX_TILES
+                            )
+                        })?;
+                }
+                if let Some(c) = parts.next() {
+                    clip_limit = c
+                        .parse()
+                        .ok()
+                        .filter(|c: &f32| c.is_finite() && *c >= 1.)
+                        .ok_or_else(|| format!("invalid CLAHE clip limit `{c}`"))?;
+                }
+                match parts.next() {
+                    Some(_) => Err(format!("too many CLAHE parameters in `{s}`")),
+                    None => Ok(Levels::Clahe { tiles, clip_limit }),
+                }
+            }
+            other => Err(format!(
+                "unknown levels mode `{other}`, expected one of absolute, equalize or clahe[:TILES[:CLIP]]"
+            )),
+        }
+    }
+}
+
+impl Display for Levels {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Levels::Absolute => f.write_str("absolute"),
+            Levels::Equalize => f.write_str("equalize"),
+            Levels::Clahe { tiles, clip_limit } => write!(f, "clahe:{tiles}:{clip_limit}"),
+        }
+    }
+}
+
+/// The brightness that picks the symbol.
+#[inline]
+pub(crate) fn brightness(pixel: &Rgba) -> u8 {
+    ((pixel.r as u16 + pixel.g as u16 + pixel.b as u16) / 3) as u8
+}
+
+/// How much the local contrast is stretched, so the soft edges get a visible symbol too.
+const DETAIL_GAIN: u32 = 2;
+
+/// The local contrast of the pixel at `x` and `y`, its brightness `v` against the average of its neighbours.
+///
+/// The flat areas are 0, and the edges and the small details are high, See: [`crate::CELL_COLORS`].
+pub(crate) fn detail<I: PixelImage + ?Sized>(
+    config: &Config,
+    image: &I,
+    x: u32,
+    y: u32,
+    v: u8,
+) -> u8 {
+    let (width, height) = image.dimensions();
+    let (mut sum, mut count) = (0u32, 0u32);
+    for ny in y.saturating_sub(1)..(y + 2).min(height) {
+        for nx in x.saturating_sub(1)..(x + 2).min(width) {
+            if (nx, ny) == (x, y) {
+                continue;
+            }
+            let pixel = config.prepare(nx, ny, image.get_pixel(nx, ny));
+            if pixel.a == 0 {
+                continue;
+            }
+            sum += brightness(&pixel) as u32;
+            count += 1;
+        }
+    }
+    if count == 0 {
+        return 0;
+    }
+    let diff = (v as i32 - (sum / count) as i32).unsigned_abs();
+    (diff * DETAIL_GAIN).min(u8::MAX as u32) as u8
+}
+
+/// The smallest CLAHE region size, in pixels (a smaller region has too few pixels for a histogram).
+const MIN_TILE_SIZE: u32 = 8;
+
+/// The brightness mapping of an image, computed from its histograms.
+#[derive(Debug, Clone)]
+pub(crate) struct ToneMap {
+    /// The mapping of every region, row by row.
+    luts: Vec<[u8; 256]>,
+    /// The regions count, on each axis.
+    tiles: (u32, u32),
+    /// The region size, in pixels.
+    tile_size: (f32, f32),
+}
+
+impl ToneMap {
+    /// Compute the mapping, or return [`None`] if the brightness is used as it is.
+    pub(crate) fn new<I: PixelImage + ?Sized>(config: &Config, image: &I) -> Option<Self> {
+        let (width, height) = image.dimensions();
+        let (tiles, clip_limit) = match config.levels {
+            Levels::Absolute => return None,
+            _ if width == 0 || height == 0 => return None,
+            Levels::Equalize => ((1, 1), None),
+            Levels::Clahe { tiles, clip_limit } => {
+                let tiles = |size: u32| {
+                    tiles
+                        .min(Levels::MAX_TILES)
+                        .min(size / MIN_TILE_SIZE)
+                        .max(1)
+                };
+                ((tiles(width), tiles(height)), Some(clip_limit))
+            }
+        };
+
+        let mut histograms = vec![[0f32; 256]; (tiles.0 * tiles.1) as usize];
+        for y in 0..height {
+            let ty = (y as u64 * tiles.1 as u64 / height as u64) as u32;
+            for x in 0..width {
+                let pixel = config.prepare(x, y, image.get_pixel(x, y));
+                // The transparent pixels (that are left to the terminal background) aren't a part of the image.
+                if pixel.a == 0 {
+                    continue;
+                }
+                let tx = (x as u64 * tiles.0 as u64 / width as u64) as u32;
+                histograms[(ty * tiles.0 + tx) as usize][brightness(&pixel) as usize] += 1.;
+            }
+        }
+
+        Some(Self {
+            luts: histograms
+                .iter_mut()
+                .map(|histogram| {
+                    if let Some(clip_limit) = clip_limit {
+                        clip(histogram, clip_limit);
+                    }
+                    equalize(histogram)
+                })
+                .collect(),
+            tiles,
+            tile_size: (
+                width as f32 / tiles.0 as f32,
+                height as f32 / tiles.1 as f32,
+            ),
+        })
+    }
+
+    /// Map the brightness of the pixel at `x` and `y`, blending the mappings of the nearest regions.
+    pub(crate) fn map(&self, x: u32, y: u32, v: u8) -> u8 {
+        if self.luts.len() == 1 {
+            return self.luts[0][v as usize];
+        }
+        let neighbours = |p: u32, size: f32, count: u32| {
+            let f = ((p as f32 + 0.5) / size - 0.5).max(0.);
+            let i0 = (f as u32).min(count - 1);
+            let i1 = (i0 + 1).min(count - 1);
+            (i0, i1, (f - i0 as f32).clamp(0., 1.))
+        };
+        let (x0, x1, tx) = neighbours(x, self.tile_size.0, self.tiles.0);
+        let (y0, y1, ty) = neighbours(y, self.tile_size.1, self.tiles.1);
+        let at = |tx: u32, ty: u32| self.luts[(ty * self.tiles.0 + tx) as usize][v as usize] as f32;
+        let top = at(x0, y0) + (at(x1, y0) - at(x0, y0)) * tx;
+        let bottom = at(x0, y1) + (at(x1, y1) - at(x0, y1)) * tx;
+        (top + (bottom - top) * ty).round() as u8
+    }
+}
+
+/// Clip the histogram bins to `clip_limit` times the average bin, and spread the excess over all the bins.
+fn clip(histogram: &mut [f32; 256], clip_limit: f32) {
+    let total: f32 = histogram.iter().sum();
+    let limit = total / 256. * clip_limit;
+    let mut excess = 0.;
+    for bin in histogram.iter_mut() {
+        if *bin > limit {
+            excess += *bin - limit;
+            *bin = limit;
+        }
+    }
+    for bin in histogram.iter_mut() {
+        *bin += excess / 256.;
+    }
+}
+
+/// The mapping that spreads the histogram over the whole range (its normalized cumulative sum).
+fn equalize(histogram: &[f32; 256]) -> [u8; 256] {
+    let mut lut = [0; 256];
+    let total: f32 = histogram.iter().sum();
+    let first = histogram.iter().copied().find(|c| *c > 0.).unwrap_or(0.);
+    if total - first <= f32::EPSILON {
+        // A single level (or an empty region), there's nothing to spread.
+        for (i, v) in lut.iter_mut().enumerate() {
+            *v = i as u8;
+        }
+        return lut;
+    }
+    let mut cdf = 0.;
+    for (v, count) in lut.iter_mut().zip(histogram) {
+        cdf += count;
+        *v = ((cdf - first).max(0.) * 255. / (total - first)).round() as u8;
+    }
+    lut
+}
@@ -84,6 +84,20 @@
 //! //  Do whatever you want with this object...
 //! ```
 //!
+//! If you only need the data, [`fragments`] iterates them lazily, so you can `collect`, `map` or `zip` them with your own data.
+//! ```no_run
+//! # #[cfg(all(feature = "image", feature = "colors"))] {
+//! # use aarty::*;
+//! let cfg = Config::new(vec!['I', 'L', 'O', 'V', 'E', 'U'].into()).with_flags(COLORS);
+//! let image = image::open("mylove.jpg").unwrap();
+//!
+//! let fragments: Vec<(char, ANSIColor)> = fragments(&cfg, &image)
+//!     .filter_map(FragmentItem::fragment)
+//!     .map(|info| (info.sym, info.fg))
+//!     .collect();
+//! # }
+//! ```
+//!
 //! But be aware by doing this, you'll have to implement the rendaring mechanism when its its the time to print the image (a.k.a. rendering it).
 //!
 //! For such this case, we have [`TextImage`], which basically dose the same thing as the code above but in more ergnomic way, And it does implement the rendering mechanism, so you can just print it, and it will render the image properly.
@@ -100,11 +114,64 @@
 //!```
 //! > You have to enable the `image` feature for this to work.
 //!
+//! If you're building a TUI with [`ratatui`](https://ratatui.rs), the `ratatui` feature provides the `widget::Image` widget,
+//! it resizes the image to the widget area, and it can draw two pixels per cell with the half blocks when the colors are enabled.
+//! ```rust,ignore
+//! use aarty::widget::Image;
+//!
+//! frame.render_widget(Image::new(&image, &cfg).half_blocks(true), area);
+//! ```
+//! A rendered [`TextImage`] can be drawn as is with `frame.render_widget(&text_image, area)`.
+//!
+//! The colors are written with the ANSI escape codes by default, implement `style::StyleBackend` to queue the styling commands into another output,
+//! and use it with `style::StyleWriter::new(out, backend)`, `text_image.write_styled(&mut out, &backend)`, `montage.styled(&backend)` or `viewer.with_backend(backend)`.
+//! The `crossterm` and `termion` features provide the `style::Crossterm` and `style::Termion` backends (`termion` is unix only).
+//!
 //! # The binary
-//! We offer a simple binary that's implement the most of this crate features. You can build it with the build command or if u use cargo then you can install it via `cargo install aarty`.
+//! We offer a simple binary that's implement the most of this crate features. You can build it with the build command or if u use cargo then you can install it via `cargo install aarty --features cli`.
 //!
 //! > **Note**
-//! > for more information about the binary and how to use it, you can run `aarty --help` or see this [match](https://github.com/0x61nas/aarty/blob/aurora/src/bin/args.rs#L59).
+//! > for more information about the binary and how to use it, you can run `aarty --help` or read its man page (See below).
+//! >
+//! > In a terminal, the colors are detected automatically and `--no-colors` (or the `NO_COLOR` variable) turns them off. The image is drawn as text by default, `--graphics auto` draws it with the terminal graphics protocol (kitty or sixel) when it's supported.
+//! >
+//! > The shell completions (bash, zsh and fish) and the man page are generated at build time into the build script `OUT_DIR`
+//! > (`target/release/build/aarty-*/out/`), they aren't installed, so copy them where your system looks for them
+//! > (e.g. `install -Dm644 target/release/build/aarty-*/out/aarty.1 ~/.local/share/man/man1/aarty.1`, then `man aarty`).
+//! >
+//! > The options that you use every time can be saved in `$XDG_CONFIG_HOME/aarty/config.toml` (or the file passed to `--config`),
+//! > the `[defaults]` table is used with every invocation, and the `[profiles.<name>]` tables can be selected with `--profile <name>`,
+//! > the command line options override both of them (e.g. `--no-reverse` turns off a `reverse = true` from the file).
+//! >
+//! > It can convert many images at once, `aarty thumbs/ 'covers/*.jpg' -o out/ --format html` converts the `thumbs` directory
+//! > recursively and the matched covers in parallel, and writes them into `out/` (100 columns wide and in truecolor, unless the size or `--depth` are given),
+//! > or `aarty thumbs/ --montage --captions` tiles them in a grid.
+//! > ```toml
+//! > [defaults]
+//! > chars = " .,-~!;:=*&%$@#"
+//! > filter = "lanczos3"
+//! >
+//! > [profiles.peach]
+//! > colors = true
+//! > reverse = true
+//! > background = "#ffd0bb"
+//! > depth = 256
+//! > ```
+//! >
+//! > `aarty view a.jpg b.png` opens the images in a full-screen viewer, pan with the arrows (or `hjkl`), zoom with `+` and `-`,
+//! > switch the images with `n` and `p`, toggle the colors with `c`, the reverse mode with `r`, cycle the presets with `s`, and quit with `q`.
+//! >
+//! > `aarty --watch plot.png` re-renders the image in place whenever the file changes, until you press `Ctrl-C`.
+//! >
+//! > `aarty scan.png --crop 400x300+120+80 --rotate 90 --flip-h` crops the image, rotates it and mirrors it before the conversion, the library has the same zero-copy views in the `view` module.
+//! >
+//! > `--brightness`, `--contrast`, `--gamma`, `--saturation` and `--invert` tune the image before it's mapped to the characters, they can be saved in the profiles too (e.g. `gamma = 1.8`).
+//! >
+//! > `--levels equalize` (or `--levels clahe`, the local variant) spreads a low-contrast image over all the characters by its brightness histogram.
+//! >
+//! > The transparent pixels are blended over `--background` (or left to the terminal background if it's not set), `--alpha checkerboard` blends them over a checkerboard instead, and `--alpha ignore` draws them opaque.
+//! >
+//! > `--cell-colors` paints every cell with its pixel color and draws the edges and the details over it in black or white, it's denser than `--reverse` where the characters still follow the brightness.
 //!
 //!
 //! # Contributing
@@ -120,20 +187,65 @@
 #![deny(rust_2018_idioms, non_ascii_idents, missing_debug_implementations)]
 #![warn(missing_docs)]
 
+/// Brightness, contrast, gamma, saturation and invert adjustments.
+pub mod adjust;
+/// Composite the transparent pixels over the background.
+pub mod alpha;
+/// Play a sequence of [`TextImage`]s in the terminal.
+#[cfg(feature = "text_image")]
+pub mod animation;
+/// Record rendered frames into asciicast v2 files.
+#[cfg(feature = "text_image")]
+pub mod asciicast;
+/// Ready [`PixelImage`]s for the raw buffers, the pixels grids and the closures.
+pub mod buffer;
+/// Detect what the terminal supports.
+#[cfg(feature = "terminal")]
+pub mod capabilities;
 /// ANSI color.
 #[cfg(feature = "colors")]
 pub mod color;
+/// Display images with the terminal graphics protocols (kitty and sixel).
+pub mod graphics;
 /// The traits implimaantions for the [`image`] crate intigration.
 #[cfg(feature = "image")]
 pub mod impl_image;
+/// The lazy fragments iterator.
+pub mod iter;
+/// Map the brightness to the symbols by the image histogram (equalisation and CLAHE).
+pub mod levels;
+/// Tile multiple [`TextImage`]s in a grid.
+#[cfg(feature = "text_image")]
+pub mod montage;
+/// Resize any [`PixelImage`] with the built-in filters.
+pub mod resample;
+/// Convert the images row by row as they're decoded.
+pub mod streaming;
+/// Write the colors with the ANSI escape codes or with other terminal libraries.
+#[cfg(feature = "colors")]
+pub mod style;
 /// The [`Sympols`] struct.
 pub mod sympols;
+/// Terminal size detection and fitting images into it.
+#[cfg(feature = "terminal")]
+pub mod terminal;
 /// The [`ToTextImage`] and [`TextImage`] stuff.
 #[cfg(feature = "text_image")]
 pub mod text_image;
+/// Raw video frame streams (YUV4MPEG2 and raw RGB).
+pub mod video;
+/// Zero-copy views over any [`PixelImage`] (crop, flip, rotate and scale).
+pub mod view;
+/// An interactive full-screen images viewer.
+#[cfg(feature = "terminal")]
+pub mod viewer;
+/// The [`ratatui`] widgets.
+#[cfg(feature = "ratatui")]
+pub mod widget;
 // Re-exports
 #[cfg(feature = "colors")]
-pub use color::ANSIColor;
+pub use color::{ANSIColor, ColorDepth, ParseColorError};
+pub use iter::{fragments, FragmentItem, Fragments};
 pub use sympols::Sympols;
 #[cfg(feature = "text_image")]
 pub use text_image::{Fragment, IndexdFragment, TextImage, ToTextImage};
@@ -146,6 +258,45 @@ use std::{error::Error, io::Write};
 pub const COLORS: u8 = 0b1;
 /// Reverse the forgruond color with the background.
 pub const REVERSE: u8 = 0b10;
+/// Paint the cells with the pixels colors, the symbols are drawn in black or white (whichever is readable),
+/// and they're picked by the local contrast instead of the brightness, so they draw the edges and the details.
+///
+/// It takes over the [`REVERSE`] flag, and it needs the [`COLORS`] flag.
+///
+/// ```
+/// # #[cfg(feature = "colors")] {
+/// # use aarty::{fragments, Config, FragmentItem, PixelImage, Rgba, Sympols, ANSIColor, CELL_COLORS, COLORS};
+/// /// A flat red square, with a white dot in the middle.
+/// struct Dot;
+///
+/// impl PixelImage for Dot {
+///     fn dimensions(&self) -> (u32, u32) {
+///         (5, 5)
+///     }
+///
+///     fn get_pixel(&self, x: u32, y: u32) -> Rgba {
+///         match (x, y) {
+///             (2, 2) => Rgba { r: 255, g: 255, b: 255, a: 255 },
+///             _ => Rgba { r: 200, g: 0, b: 0, a: 255 },
+///         }
+///     }
+/// }
+///
+/// let config = Config::new(Sympols::new(vec![' ', '.', 'o', '@'])).with_flags(COLORS | CELL_COLORS);
+/// let syms: Vec<_> = fragments(&config, &Dot).filter_map(FragmentItem::fragment).map(|i| i.sym).collect();
+/// // The flat area is blank (only its color is shown), the dot stands out.
+/// assert_eq!(syms[0], ' ');
+/// assert_eq!(syms[2 * 5 + 2], '@');
+///
+/// // The symbols are white over the dark red cells.
+/// let mut out = Vec::new();
+/// aarty::convert_image_to_ascii(&config, &Dot, &mut out).unwrap();
+/// let out = String::from_utf8(out).unwrap();
+/// assert!(out.contains(&ANSIColor::new(200, 0, 0).as_background()));
+/// assert!(out.contains(&ANSIColor::new(255, 255, 255).to_string()));
+/// # }
+/// ```
+pub const CELL_COLORS: u8 = 0b100;
 
 /// Trait that represent the (normal) images, that we wanna transform them.
 pub trait PixelImage {
@@ -181,8 +332,7 @@ impl<W: Write> FragmentWriter for W {
     #[cfg(feature = "colors")]
     #[inline]
     fn background(&mut self, bc: &ANSIColor) -> Result<bool, Box<dyn Error>> {
-        self.write_bytes(bc.to_string().as_bytes())?;
-        Ok(true)
+        style::write_background(self, &style::Ansi, bc)
     }
 
     #[inline]
@@ -205,22 +355,7 @@ impl<W: Write> FragmentWriter for W {
         bc: Option<&ANSIColor>,
         fc: Option<&ANSIColor>,
     ) -> Result<(), Box<dyn Error>> {
-        if let Some(bc) = bc {
-            self.write_all(bc.as_background().as_bytes())?;
-        }
-        if let Some(fc) = fc {
-            self.write_all(fc.as_foreground().as_bytes())?;
-        }
-
-        self.write_fmt(format_args!("{}", info.sym))?;
-
-        if bc.is_some() {
-            self.write_all(ANSI_ESCAPE_CLOSE.as_bytes())?;
-        }
-        if fc.is_some() {
-            self.write_all(ANSI_ESCAPE_CLOSE.as_bytes())?;
-        }
-        Ok(())
+        style::write_colored_fragment(self, &style::Ansi, info, bc, fc)
     }
 }
 
@@ -234,8 +369,17 @@ pub struct Config {
     /// The result image background, useful if the image has a transparent parts or if you plan to use the [`REVERSE`] flag.
     #[cfg(feature = "colors")]
     pub background: Option<ANSIColor>,
+    /// The colors get reduced to what this depth can display.
+    #[cfg(feature = "colors")]
+    pub color_depth: ColorDepth,
     /// the boolean flags.
     pub flags: u8,
+    /// The adjustments that are applied to every pixel before it's converted.
+    pub adjustments: adjust::Adjustments,
+    /// How the brightness is mapped to the symbols.
+    pub levels: levels::Levels,
+    /// How the transparent pixels are drawn.
+    pub alpha: alpha::Alpha,
 }
 
 impl Config {
@@ -247,7 +391,12 @@ impl Config {
             sympols,
             #[cfg(feature = "colors")]
             background: None,
+            #[cfg(feature = "colors")]
+            color_depth: ColorDepth::TrueColor,
             flags: 0,
+            adjustments: adjust::Adjustments::NONE,
+            levels: levels::Levels::Absolute,
+            alpha: alpha::Alpha::Blend,
         }
     }
 
@@ -259,6 +408,41 @@ impl Config {
         self
     }
 
+    /// Set the adjustments.
+    #[inline]
+    #[must_use]
+    pub const fn with_adjustments(mut self, adjustments: adjust::Adjustments) -> Self {
+        self.adjustments = adjustments;
+        self
+    }
+
+    /// Set how the brightness is mapped to the symbols.
+    #[inline]
+    #[must_use]
+    pub const fn with_levels(mut self, levels: levels::Levels) -> Self {
+        self.levels = levels;
+        self
+    }
+
+    /// Set how the transparent pixels are drawn.
+    #[inline]
+    #[must_use]
+    pub const fn with_alpha(mut self, alpha: alpha::Alpha) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Adjust the pixel at `x` and `y`, then composite it over the background.
+    #[inline]
+    pub(crate) fn prepare(&self, x: u32, y: u32, pixel: Rgba) -> Rgba {
+        #[cfg(feature = "colors")]
+        let background = self.background.as_ref().and_then(ANSIColor::rgb);
+        #[cfg(not(feature = "colors"))]
+        let background = None;
+        self.alpha
+            .composite(self.adjustments.apply(pixel), x, y, background)
+    }
+
     /// Calculate how much space the raw representation would use (the worst case).
     pub const fn calc_buf_size(&self, w: u32, h: u32) -> usize {
         #[allow(unused_mut)]
@@ -294,10 +478,30 @@ impl Config {
         Self {
             sympols,
             background: Some(background_color),
+            color_depth: ColorDepth::TrueColor,
             flags: 0,
+            adjustments: adjust::Adjustments::NONE,
+            levels: levels::Levels::Absolute,
+            alpha: alpha::Alpha::Blend,
         }
     }
 
+    /// Set the color depth.
+    #[inline]
+    #[must_use]
+    pub const fn with_color_depth(mut self, depth: ColorDepth) -> Self {
+        self.color_depth = depth;
+        self
+    }
+
+    /// The background color reduced to the config color depth.
+    #[inline]
+    pub(crate) fn background_color(&self) -> Option<ANSIColor> {
+        self.background
+            .as_ref()
+            .map(|bc| bc.with_depth(self.color_depth))
+    }
+
     /// return true if the [`REVERSE`] flag is set.
     #[inline]
     pub const fn reversed(&self) -> bool {
@@ -309,6 +513,34 @@ impl Config {
     pub const fn use_colors(&self) -> bool {
         self.flags & COLORS == COLORS
     }
+
+    /// return true if both the [`CELL_COLORS`] and the [`COLORS`] flags are set.
+    #[inline]
+    pub const fn cell_colors(&self) -> bool {
+        self.flags & (CELL_COLORS | COLORS) == CELL_COLORS | COLORS
+    }
+
+    /// The background and the foreground of a fragment cell, with the [`REVERSE`] or the [`CELL_COLORS`] mode applied.
+    pub(crate) fn cell_style(
+        &self,
+        fg: &ANSIColor,
+        background: Option<ANSIColor>,
+    ) -> (Option<ANSIColor>, Option<ANSIColor>) {
+        if self.cell_colors() {
+            if fg.is_transparent() {
+                return (background, Some(fg.clone()));
+            }
+            return (
+                Some(fg.clone()),
+                Some(fg.contrast().with_depth(self.color_depth)),
+            );
+        }
+        if self.reversed() {
+            (Some(fg.clone()), background)
+        } else {
+            (background, Some(fg.clone()))
+        }
+    }
 }
 
 /// the fragment (a.k.a. pixel) information.
@@ -326,6 +558,47 @@ pub struct FragmentInfo {
     pub fg: ANSIColor,
 }
 
+impl FragmentInfo {
+    /// The fragment of the pixel at `x` and `y`, the color is reduced to the config depth if the colors are enabled.
+    #[inline]
+    pub(crate) fn new(config: &Config, x: u32, y: u32, pixel: Rgba) -> Self {
+        Self::mapped(config, x, y, pixel, |v| v)
+    }
+
+    /// The fragment of the pixel at `x` and `y`, its symbol is picked by the `tone` of its brightness.
+    #[inline]
+    pub(crate) fn mapped<F: FnOnce(u8) -> u8>(
+        config: &Config,
+        x: u32,
+        y: u32,
+        pixel: Rgba,
+        tone: F,
+    ) -> Self {
+        let pixel = config.prepare(x, y, pixel);
+        // The transparent pixels are left blank.
+        let v = match pixel.a {
+            0 => 0,
+            _ => tone(levels::brightness(&pixel)),
+        };
+        let (sym, sym_index) = config.sympols.sym_and_index(&Rgba {
+            r: v,
+            g: v,
+            b: v,
+            a: pixel.a,
+        });
+        Self {
+            sym,
+            sym_index,
+            #[cfg(feature = "colors")]
+            fg: if config.use_colors() {
+                ANSIColor::from(pixel).with_depth(config.color_depth)
+            } else {
+                pixel.into()
+            },
+        }
+    }
+}
+
 /// RGBA pixel.
 #[derive(Debug, PartialEq, PartialOrd, Clone, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -354,61 +627,91 @@ where
     I: PixelImage,
     W: FragmentWriter,
 {
-    let (width, height) = image.dimensions();
-    #[cfg(feature = "colors")]
-    let ansi_close = if let Some(bc) = &config.background {
-        if !config.reversed() {
-            out.background(bc)?
-        } else {
-            false
+    let emitter = Emitter::begin(config, out)?;
+    for item in fragments(config, image) {
+        match item {
+            FragmentItem::Fragment { info, .. } => emitter.write(out, info)?,
+            FragmentItem::RowEnd { .. } => emitter.end_row(out)?,
         }
-    } else {
-        false
-    };
+    }
+    emitter.finish(out)
+}
 
+/// Write the converted pixels to a [`FragmentWriter`] one by one.
+pub(crate) struct Emitter<'a> {
+    config: &'a Config,
     #[cfg(feature = "colors")]
-    let colored = config.use_colors();
-
-    // FIXME: i don't like this way
-    #[cfg(not(feature = "colors"))]
-    let colored = false;
-
-    for y in 0..height {
-        for x in 0..width {
-            let pixel = image.get_pixel(x, y);
-            if colored {
-                #[cfg(feature = "colors")]
-                {
-                    let (sym, sym_index) = config.sympols.sym_and_index(&pixel);
-                    let fi = FragmentInfo {
-                        sym,
-                        sym_index,
-                        fg: ANSIColor::from(pixel),
-                    };
-                    let mut fg = Some(fi.fg.clone());
-                    let mut bc = config.background.clone();
-                    if !ansi_close && config.reversed() {
-                        std::mem::swap(&mut bc, &mut fg);
-                    }
-                    out.write_colored_fragment(fi, bc.as_ref(), fg.as_ref())?;
-                }
-            } else {
-                let (sym, sym_index) = config.sympols.sym_and_index(&pixel);
-                out.write_fragment(FragmentInfo {
-                    sym,
-                    sym_index,
-                    #[cfg(feature = "colors")]
-                    fg: pixel.into(),
-                })?;
-            }
+    background: Option<ANSIColor>,
+    /// The writer took the background, so it should be closed at the end.
+    #[cfg(feature = "colors")]
+    ansi_close: bool,
+}
+
+impl<'a> Emitter<'a> {
+    /// Write the image background (if the writer takes it).
+    pub(crate) fn begin<W: FragmentWriter>(
+        config: &'a Config,
+        #[allow(unused_variables)] out: &mut W,
+    ) -> Result<Self, Box<dyn Error>> {
+        #[cfg(feature = "colors")]
+        let background = config.background_color();
+        #[cfg(feature = "colors")]
+        let ansi_close = match &background {
+            Some(bc) if !config.reversed() || config.cell_colors() => out.background(bc)?,
+            _ => false,
+        };
+        Ok(Self {
+            config,
+            #[cfg(feature = "colors")]
+            background,
+            #[cfg(feature = "colors")]
+            ansi_close,
+        })
+    }
+
+    /// Write the pixel at `x` and `y`.
+    #[inline]
+    pub(crate) fn pixel<W: FragmentWriter>(
+        &self,
+        out: &mut W,
+        x: u32,
+        y: u32,
+        pixel: Rgba,
+    ) -> Result<(), Box<dyn Error>> {
+        self.write(out, FragmentInfo::new(self.config, x, y, pixel))
+    }
+
+    /// Write a fragment, with the background and the reverse (or the cells colors) mode applied.
+    #[inline]
+    pub(crate) fn write<W: FragmentWriter>(
+        &self,
+        out: &mut W,
+        info: FragmentInfo,
+    ) -> Result<(), Box<dyn Error>> {
+        #[cfg(feature = "colors")]
+        if self.config.use_colors() {
+            let (bc, fg) = self.config.cell_style(&info.fg, self.background.clone());
+            return out.write_colored_fragment(info, bc.as_ref(), fg.as_ref());
         }
-        out.write_bytes("\n".as_bytes())?;
+
+        out.write_fragment(info)
     }
 
-    #[cfg(feature = "colors")]
-    if ansi_close {
-        out.write_bytes(ANSI_ESCAPE_CLOSE.as_bytes())?;
+    /// End the current row.
+    #[inline]
+    pub(crate) fn end_row<W: FragmentWriter>(&self, out: &mut W) -> Result<(), Box<dyn Error>> {
+        out.write_bytes("\n".as_bytes())
     }
 
-    Ok(())
+    /// Close the background.
+    pub(crate) fn finish<W: FragmentWriter>(
+        self,
+        #[allow(unused_variables)] out: &mut W,
+    ) -> Result<(), Box<dyn Error>> {
+        #[cfg(feature = "colors")]
+        if self.ansi_close {
+            out.write_bytes(ANSI_ESCAPE_CLOSE.as_bytes())?;
+        }
+        Ok(())
+    }
 }
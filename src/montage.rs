@@ -0,0 +1,280 @@
+use core::fmt;
+use std::fmt::Display;
+
+use crate::{Fragment, TextImage};
+
+#[cfg(feature = "colors")]
+use crate::style::{Ansi, Command, FmtWriter, StyleBackend};
+
+/// Write a single cell of a tile.
+type CellWriter<'a> = dyn Fn(&mut fmt::Formatter<'_>, &TextImage, &Fragment) -> fmt::Result + 'a;
+
+/// A contact sheet, it tiles multiple [`TextImage`]s in a grid.
+///
+/// Every tile gets the same cell size (the size of the largest image), the smaller images are centered in their cells,
+/// and the captions are written under the images, truncated to the cell width.
+///
+/// ```
+/// # use aarty::{montage::Montage, Config, PixelImage, Rgba, Sympols, ToTextImage};
+/// struct Solid(u32, u32, u8);
+///
+/// impl PixelImage for Solid {
+///     fn dimensions(&self) -> (u32, u32) {
+///         (self.0, self.1)
+///     }
+///
+///     fn get_pixel(&self, _: u32, _: u32) -> Rgba {
+///         Rgba { r: self.2, g: self.2, b: self.2, a: 255 }
+///     }
+/// }
+///
+/// let config = Config::new(Sympols::new(vec![' ', '#']));
+/// let mut montage = Montage::new(2).with_gutter(1, 1);
+/// montage.push(Solid(4, 2, 255).to_text(config.clone()), Some("a.png"));
+/// montage.push(Solid(2, 1, 255).to_text(config.clone()), Some("long_name.png"));
+/// montage.push(Solid(4, 2, 255).to_text(config), None::<&str>);
+///
+/// assert_eq!(montage.cell_size(), (4, 2));
+/// let lines: Vec<_> = montage.to_string().lines().map(String::from).collect();
+/// assert_eq!(
+///     lines,
+///     [
+///         "####  ## ",
+///         "####     ",
+///         "a.pn long",
+///         "         ",
+///         "####     ",
+///         "####     ",
+///         "         ",
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Montage {
+    tiles: Vec<(TextImage, Option<String>)>,
+    columns: usize,
+    /// The spaces between the columns.
+    column_gutter: usize,
+    /// The empty lines between the rows.
+    row_gutter: usize,
+}
+
+impl Montage {
+    /// Construct a new empty montage with the given columns number.
+    #[inline]
+    pub fn new(columns: usize) -> Self {
+        Self {
+            tiles: Vec::new(),
+            columns: columns.max(1),
+            column_gutter: 1,
+            row_gutter: 1,
+        }
+    }
+
+    /// Set the gutters, the spaces between the columns and the empty lines between the rows.
+    #[inline]
+    #[must_use]
+    pub fn with_gutter(mut self, columns: usize, rows: usize) -> Self {
+        self.column_gutter = columns;
+        self.row_gutter = rows;
+        self
+    }
+
+    /// Add an image, with an optional caption (e.g. the file name).
+    #[inline]
+    pub fn push(&mut self, image: TextImage, caption: Option<impl Into<String>>) {
+        self.tiles.push((image, caption.map(Into::into)));
+    }
+
+    /// The tiles number.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Return true if there's no tiles.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// The cell size (in columns and rows) that every image gets, without the caption.
+    pub fn cell_size(&self) -> (usize, usize) {
+        self.tiles.iter().fold((0, 0), |(w, h), (image, _)| {
+            (w.max(image.row_len), h.max(image.rows()))
+        })
+    }
+}
+
+#[cfg(feature = "colors")]
+impl Montage {
+    /// Display the montage with the colors written by the given backend, instead of the ANSI escape codes.
+    ///
+    /// ```
+    /// # use aarty::{montage::Montage, style::Ansi, Config, IndexdFragment, Sympols, TextImage, ANSIColor, COLORS};
+    /// let mut image = TextImage::new(Config::new(Sympols::new(vec!['#'])).with_flags(COLORS), 1, 1);
+    /// image.insert(0, IndexdFragment::new_with_foreground(0, ANSIColor::new(255, 0, 0)));
+    /// let mut montage = Montage::new(2);
+    /// montage.push(image, None::<&str>);
+    /// assert_eq!(montage.styled(&Ansi).to_string(), montage.to_string());
+    /// ```
+    #[inline]
+    pub fn styled<'a, B: StyleBackend>(&'a self, backend: &'a B) -> Styled<'a, B> {
+        Styled {
+            montage: self,
+            backend,
+        }
+    }
+}
+
+/// Display a [`Montage`] with a [`StyleBackend`], See: [`Montage::styled`].
+#[cfg(feature = "colors")]
+#[derive(Debug)]
+pub struct Styled<'a, B> {
+    montage: &'a Montage,
+    backend: &'a B,
+}
+
+#[cfg(feature = "colors")]
+impl<B: StyleBackend> Display for Styled<'_, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.montage._fmt(f, &|f, image, frag| {
+            write_cell(f, self.backend, image, frag)
+        })
+    }
+}
+
+impl Display for Montage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(feature = "colors")]
+        {
+            self._fmt(f, &|f, image, frag| write_cell(f, &Ansi, image, frag))
+        }
+
+        #[cfg(not(feature = "colors"))]
+        self._fmt(f, &|f, _, frag| write!(f, "{}", frag.sym()))
+    }
+}
+
+impl Montage {
+    fn _fmt(&self, f: &mut fmt::Formatter<'_>, cell: &CellWriter<'_>) -> fmt::Result {
+        let (width, height) = self.cell_size();
+        let captions = self.tiles.iter().any(|(_, caption)| caption.is_some());
+
+        for (i, row) in self.tiles.chunks(self.columns).enumerate() {
+            if i > 0 {
+                for _ in 0..self.row_gutter {
+                    writeln!(f, "{:w$}", "", w = self.row_width(width))?;
+                }
+            }
+            for y in 0..height {
+                for (j, (image, _)) in row.iter().enumerate() {
+                    if j > 0 {
+                        write!(f, "{:w$}", "", w = self.column_gutter)?;
+                    }
+                    self.write_tile_row(f, image, y, (width, height), cell)?;
+                }
+                self.pad_row(f, row.len(), width)?;
+                writeln!(f)?;
+            }
+            if captions {
+                for (j, (_, caption)) in row.iter().enumerate() {
+                    if j > 0 {
+                        write!(f, "{:w$}", "", w = self.column_gutter)?;
+                    }
+                    let caption: String = caption
+                        .as_deref()
+                        .unwrap_or("")
+                        .chars()
+                        .take(width)
+                        .collect();
+                    let (left, right) = split_padding(width, caption.chars().count());
+                    write!(f, "{:left$}{caption}{:right$}", "", "")?;
+                }
+                self.pad_row(f, row.len(), width)?;
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Montage {
+    /// The whole montage width.
+    #[inline]
+    fn row_width(&self, width: usize) -> usize {
+        let columns = self.columns.min(self.tiles.len());
+        columns * width + columns.saturating_sub(1) * self.column_gutter
+    }
+
+    /// Write a row of the image centered in its cell.
+    fn write_tile_row(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        image: &TextImage,
+        y: usize,
+        (width, height): (usize, usize),
+        cell: &CellWriter<'_>,
+    ) -> fmt::Result {
+        let (left, right) = split_padding(width, image.row_len);
+        let (top, _) = split_padding(height, image.rows());
+        if y < top || y - top >= image.rows() {
+            return write!(f, "{:width$}", "");
+        }
+        let y = y - top;
+
+        write!(f, "{:left$}", "")?;
+        for x in 0..image.row_len {
+            let Some(frag) = image.get(y * image.row_len + x) else {
+                f.write_str(" ")?;
+                continue;
+            };
+            cell(f, image, &frag)?;
+        }
+        write!(f, "{:right$}", "")
+    }
+
+    /// Fill the missing tiles in the last row, so all the lines have the same width.
+    #[inline]
+    fn pad_row(&self, f: &mut fmt::Formatter<'_>, tiles: usize, width: usize) -> fmt::Result {
+        let missing = self.columns.min(self.tiles.len()) - tiles;
+        if missing > 0 {
+            write!(f, "{:w$}", "", w = missing * (width + self.column_gutter))?;
+        }
+        Ok(())
+    }
+}
+
+/// Write a cell with its colors queued into the backend (if the image has the colors).
+#[cfg(feature = "colors")]
+fn write_cell(
+    f: &mut fmt::Formatter<'_>,
+    backend: &dyn StyleBackend,
+    image: &TextImage,
+    frag: &Fragment,
+) -> fmt::Result {
+    if !image.config.use_colors() {
+        return write!(f, "{}", frag.sym());
+    }
+    let out = &mut FmtWriter(f);
+    let (bc, fc) = image
+        .config
+        .cell_style(frag.foreground(), image.config.background_color());
+    let mut sym = [0; 4];
+    let mut queue = |command| backend.queue(out, command).map_err(|_| fmt::Error);
+    if let Some(bc) = &bc {
+        queue(Command::SetBackground(bc))?;
+    }
+    if let Some(fc) = &fc {
+        queue(Command::SetForeground(fc))?;
+    }
+    queue(Command::Print(frag.sym().encode_utf8(&mut sym)))?;
+    queue(Command::Reset)
+}
+
+/// Split the free space around the content to center it.
+#[inline]
+fn split_padding(space: usize, content: usize) -> (usize, usize) {
+    let free = space.saturating_sub(content);
+    (free / 2, free - free / 2)
+}
@@ -0,0 +1,246 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{buffer::PixelGrid, PixelImage, Rgba};
+
+/// An empty source is resized to transparent pixels.
+const TRANSPARENT: Rgba = Rgba {
+    r: 0,
+    g: 0,
+    b: 0,
+    a: 0,
+};
+
+/// How the source pixels are picked (or mixed) when an image is resized.
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Sampler {
+    /// The nearest source pixel, the fastest.
+    #[default]
+    Nearest,
+    /// The average of the source pixels that the target pixel covers, weighted by their covered area.
+    Box,
+    /// The linear interpolation of the neighbour pixels (a triangle filter when downsampling).
+    Bilinear,
+    /// The Lanczos filter with 3 lobes, the sharpest and the slowest.
+    Lanczos3,
+}
+
+impl FromStr for Sampler {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nearest" => Ok(Sampler::Nearest),
+            "box" | "area" => Ok(Sampler::Box),
+            "bilinear" | "triangle" => Ok(Sampler::Bilinear),
+            "lanczos3" | "lanczos" => Ok(Sampler::Lanczos3),
+            other => Err(format!(
+                "unknown sampler `{other}`, expected one of nearest, box, bilinear or lanczos3"
+            )),
+        }
+    }
+}
+
+impl Display for Sampler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Sampler::Nearest => "nearest",
+            Sampler::Box => "box",
+            Sampler::Bilinear => "bilinear",
+            Sampler::Lanczos3 => "lanczos3",
+        })
+    }
+}
+
+/// Resize an image into a new [`PixelGrid`], it works with any [`PixelImage`] (no need for the `image` feature).
+///
+/// The colors are mixed with a premultiplied alpha, so the transparent pixels don't leak their colors.
+///
+/// ```
+/// # use aarty::{buffer::PixelGrid, resample::{resize, Sampler}, PixelImage, Rgba};
+/// let black = Rgba { r: 0, g: 0, b: 0, a: 255 };
+/// let white = Rgba { r: 254, g: 254, b: 254, a: 255 };
+/// // Black and white columns.
+/// let pixels = (0..16).map(|i| if i % 2 == 0 { black.clone() } else { white.clone() });
+/// let image = PixelGrid::from_pixels(4, pixels.collect()).unwrap();
+///
+/// let averaged = resize(&image, (2, 2), Sampler::Box);
+/// assert_eq!(averaged.dimensions(), (2, 2));
+/// assert!(averaged.as_pixels().iter().all(|p| p.r == 127));
+///
+/// // The upscaled edges are smooth.
+/// let image = PixelGrid::from_pixels(2, vec![black, white]).unwrap();
+/// let upscaled = resize(&image, (8, 1), Sampler::Bilinear);
+/// let reds: Vec<_> = upscaled.as_pixels().iter().map(|p| p.r).collect();
+/// assert_eq!(reds, [0, 0, 32, 95, 159, 222, 254, 254]);
+/// assert_eq!(resize(&image, (8, 1), Sampler::Nearest).get_pixel(3, 0).r, 0);
+/// ```
+pub fn resize<I: PixelImage + ?Sized>(image: &I, size: (u32, u32), sampler: Sampler) -> PixelGrid {
+    let (sw, sh) = image.dimensions();
+    let (tw, th) = size;
+    if sw == 0 || sh == 0 {
+        return PixelGrid::new(tw, th, TRANSPARENT);
+    }
+
+    let mut pixels = Vec::with_capacity(tw as usize * th as usize);
+    if sampler == Sampler::Nearest {
+        for y in 0..th {
+            let sy = nearest(y, sh, th);
+            pixels.extend((0..tw).map(|x| image.get_pixel(nearest(x, sw, tw), sy)));
+        }
+    } else {
+        // The filters are separable, so the rows are resized first, then the columns.
+        let columns: Vec<_> = (0..tw).map(|x| Taps::new(x, sw, tw, sampler)).collect();
+        let mut rows = Vec::with_capacity(tw as usize * sh as usize);
+        let mut line = Vec::with_capacity(sw as usize);
+        for y in 0..sh {
+            line.clear();
+            line.extend((0..sw).map(|x| premultiply(image.get_pixel(x, y))));
+            rows.extend(columns.iter().map(|taps| taps.apply(|i| line[i as usize])));
+        }
+        for y in 0..th {
+            let taps = Taps::new(y, sh, th, sampler);
+            pixels.extend(
+                (0..tw as usize)
+                    .map(|x| unpremultiply(taps.apply(|j| rows[j as usize * tw as usize + x]))),
+            );
+        }
+    }
+    PixelGrid::from_pixels(tw, pixels).expect("the pixels are exactly the target size")
+}
+
+/// Sample a single pixel of the image as if it was resized to `size`, See: [`crate::view::Scaled`].
+pub(crate) fn sample<I: PixelImage + ?Sized>(
+    image: &I,
+    x: u32,
+    y: u32,
+    size: (u32, u32),
+    sampler: Sampler,
+) -> Rgba {
+    let (sw, sh) = image.dimensions();
+    if sw == 0 || sh == 0 {
+        return TRANSPARENT;
+    }
+    if sampler == Sampler::Nearest {
+        return image.get_pixel(nearest(x, sw, size.0), nearest(y, sh, size.1));
+    }
+    let columns = Taps::new(x, sw, size.0, sampler);
+    let rows = Taps::new(y, sh, size.1, sampler);
+    unpremultiply(rows.apply(|j| columns.apply(|i| premultiply(image.get_pixel(i, j)))))
+}
+
+/// The source pixel under the center of a target pixel.
+#[inline]
+fn nearest(v: u32, source: u32, target: u32) -> u32 {
+    ((v as u64 * 2 + 1) * source as u64 / (target as u64 * 2)) as u32
+}
+
+/// The source pixels that a target pixel mixes, and their weights.
+struct Taps {
+    start: u32,
+    weights: Vec<f32>,
+}
+
+impl Taps {
+    fn new(v: u32, source: u32, target: u32, sampler: Sampler) -> Self {
+        let scale = source as f32 / target as f32;
+        let center = (v as f32 + 0.5) * scale;
+        let (start, weights): (u32, Vec<f32>) = match sampler {
+            Sampler::Nearest => (nearest(v, source, target), vec![1.]),
+            Sampler::Box => {
+                let (x0, x1) = (v as f32 * scale, (v + 1) as f32 * scale);
+                let start = (x0.floor() as u32).min(source - 1);
+                let end = (x1.ceil() as u32).clamp(start + 1, source);
+                let weights = (start..end)
+                    .map(|i| (x1.min(i as f32 + 1.) - x0.max(i as f32)).max(0.))
+                    .collect();
+                (start, weights)
+            }
+            Sampler::Bilinear | Sampler::Lanczos3 => {
+                let (radius, kernel): (f32, fn(f32) -> f32) = match sampler {
+                    Sampler::Bilinear => (1., triangle),
+                    _ => (3., lanczos3),
+                };
+                // Widen the kernel when downsampling, so every source pixel counts.
+                let stretch = scale.max(1.);
+                let support = radius * stretch;
+                let start = ((center - support).floor().max(0.) as u32).min(source - 1);
+                let end = ((center + support).ceil() as u32).clamp(start + 1, source);
+                let weights = (start..end)
+                    .map(|i| kernel((i as f32 + 0.5 - center) / stretch))
+                    .collect();
+                (start, weights)
+            }
+        };
+
+        let sum: f32 = weights.iter().sum();
+        if sum.abs() < f32::EPSILON {
+            return Self {
+                start: nearest(v, source, target),
+                weights: vec![1.],
+            };
+        }
+        Self {
+            start,
+            weights: weights.into_iter().map(|w| w / sum).collect(),
+        }
+    }
+
+    /// The weighted sum of the pixels, `pixel` gets the source coordinate.
+    #[inline]
+    fn apply<F: FnMut(u32) -> [f32; 4]>(&self, mut pixel: F) -> [f32; 4] {
+        let mut sum = [0.; 4];
+        for (i, w) in (self.start..).zip(&self.weights) {
+            for (s, v) in sum.iter_mut().zip(pixel(i)) {
+                *s += v * w;
+            }
+        }
+        sum
+    }
+}
+
+#[inline]
+fn triangle(x: f32) -> f32 {
+    (1. - x.abs()).max(0.)
+}
+
+#[inline]
+fn lanczos3(x: f32) -> f32 {
+    fn sinc(x: f32) -> f32 {
+        let x = x * std::f32::consts::PI;
+        x.sin() / x
+    }
+
+    match x.abs() {
+        x if x < f32::EPSILON => 1.,
+        x if x < 3. => sinc(x) * sinc(x / 3.),
+        _ => 0.,
+    }
+}
+
+#[inline]
+fn premultiply(p: Rgba) -> [f32; 4] {
+    let a = p.a as f32 / 255.;
+    [p.r as f32 * a, p.g as f32 * a, p.b as f32 * a, p.a as f32]
+}
+
+#[inline]
+fn unpremultiply([r, g, b, a]: [f32; 4]) -> Rgba {
+    let a = a.round().clamp(0., 255.);
+    if a == 0. {
+        return Rgba {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 0,
+        };
+    }
+    let channel = |v: f32| (v * 255. / a).round().clamp(0., 255.) as u8;
+    Rgba {
+        r: channel(r),
+        g: channel(g),
+        b: channel(b),
+        a: a as u8,
+    }
+}
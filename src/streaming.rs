@@ -0,0 +1,173 @@
+use std::error::Error;
+
+use crate::{Config, Emitter, FragmentWriter, Rgba};
+
+/// Convert an image row by row, without holding the whole image in memory.
+///
+/// The rows are averaged into the target grid as they're pushed, and every completed text row is written
+/// to the [`FragmentWriter`] right away, so only one text row of sums is kept.
+///
+/// The target size is clamped to the source size, it only downsamples.
+///
+/// The adaptive [`crate::levels::Levels`] need the whole image, so they're ignored here, and the [`crate::CELL_COLORS`]
+/// symbols are picked by the brightness (the neighbour rows aren't read yet).
+///
+/// ```
+/// # use aarty::{streaming::RowConverter, Config, Rgba, Sympols};
+/// let black = Rgba { r: 0, g: 0, b: 0, a: 255 };
+/// let white = Rgba { r: 255, g: 255, b: 255, a: 255 };
+///
+/// let config = Config::new(Sympols::new(vec![' ', '#']));
+/// // A 4x4 image into a 2x2 grid.
+/// let mut rows = RowConverter::new(&config, (4, 4), (2, 2), Vec::new()).unwrap();
+/// for _ in 0..4 {
+///     rows.push_row(&[black.clone(), black.clone(), white.clone(), white.clone()]).unwrap();
+/// }
+/// let out = rows.finish().unwrap();
+/// assert_eq!(String::from_utf8(out).unwrap(), " #\n #\n");
+/// ```
+pub struct RowConverter<'a, W> {
+    out: W,
+    emitter: Emitter<'a>,
+    source: (u32, u32),
+    target: (u32, u32),
+    /// The next source row.
+    y: u32,
+    /// The target row that the sums belong to.
+    row: u32,
+    /// The red, green, blue and alpha sums, and the pixels count of every target column.
+    sums: Vec<[u64; 5]>,
+}
+
+impl<'a, W: FragmentWriter> RowConverter<'a, W> {
+    /// Construct a new converter for an image with the `source` size (width and height), that gets converted into the `target` size (columns and rows).
+    ///
+    /// The image background is written to `out` right away.
+    pub fn new(
+        config: &'a Config,
+        source: (u32, u32),
+        target: (u32, u32),
+        mut out: W,
+    ) -> Result<Self, Box<dyn Error>> {
+        if source.0 == 0 || source.1 == 0 {
+            return Err("can't convert an empty image".into());
+        }
+        let target = (target.0.clamp(1, source.0), target.1.clamp(1, source.1));
+        let emitter = Emitter::begin(config, &mut out)?;
+        Ok(Self {
+            out,
+            emitter,
+            source,
+            target,
+            y: 0,
+            row: 0,
+            sums: vec![[0; 5]; target.0 as usize],
+        })
+    }
+
+    /// The output size, in columns and rows.
+    #[inline]
+    pub fn target(&self) -> (u32, u32) {
+        self.target
+    }
+
+    /// How many source rows are still expected.
+    #[inline]
+    pub fn remaining(&self) -> u32 {
+        self.source.1 - self.y
+    }
+
+    /// Push the next source row, it should be exactly as wide as the source image.
+    pub fn push_row(&mut self, pixels: &[Rgba]) -> Result<(), Box<dyn Error>> {
+        if pixels.len() != self.source.0 as usize {
+            return Err(format!(
+                "expected a row of {} pixels, got {}",
+                self.source.0,
+                pixels.len()
+            )
+            .into());
+        }
+        if self.y == self.source.1 {
+            return Err(format!("the image has only {} rows", self.source.1).into());
+        }
+
+        let row = scale(self.y, self.source.1, self.target.1);
+        if row != self.row {
+            self.flush()?;
+            self.row = row;
+        }
+        for (x, pixel) in pixels.iter().enumerate() {
+            let sum = &mut self.sums[scale(x as u32, self.source.0, self.target.0) as usize];
+            for (s, v) in sum.iter_mut().zip([pixel.r, pixel.g, pixel.b, pixel.a, 1]) {
+                *s += v as u64;
+            }
+        }
+        self.y += 1;
+        if self.y == self.source.1 {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Push a strip of source rows, its length should be a multiple of the source image width.
+    pub fn push_rows(&mut self, pixels: &[Rgba]) -> Result<(), Box<dyn Error>> {
+        let width = self.source.0 as usize;
+        if pixels.len() % width != 0 {
+            return Err(format!(
+                "a strip of {} pixels isn't made of {width} pixels rows",
+                pixels.len()
+            )
+            .into());
+        }
+        pixels.chunks(width).try_for_each(|row| self.push_row(row))
+    }
+
+    /// Close the image and return the writer.
+    ///
+    /// Return an error if some of the source rows weren't pushed.
+    pub fn finish(mut self) -> Result<W, Box<dyn Error>> {
+        if self.y != self.source.1 {
+            return Err(format!(
+                "the image was finished after {} of {} rows",
+                self.y, self.source.1
+            )
+            .into());
+        }
+        self.emitter.finish(&mut self.out)?;
+        Ok(self.out)
+    }
+
+    /// Write the averaged target row, and reset the sums.
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        for (x, sum) in self.sums.iter_mut().enumerate() {
+            let [r, g, b, a, count] = *sum;
+            let count = count.max(1);
+            let pixel = Rgba {
+                r: (r / count) as u8,
+                g: (g / count) as u8,
+                b: (b / count) as u8,
+                a: (a / count) as u8,
+            };
+            self.emitter
+                .pixel(&mut self.out, x as u32, self.row, pixel)?;
+            *sum = [0; 5];
+        }
+        self.emitter.end_row(&mut self.out)
+    }
+}
+
+impl<W> std::fmt::Debug for RowConverter<'_, W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RowConverter")
+            .field("source", &self.source)
+            .field("target", &self.target)
+            .field("y", &self.y)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Map a source coordinate into the target grid.
+#[inline]
+fn scale(v: u32, source: u32, target: u32) -> u32 {
+    (v as u64 * target as u64 / source as u64) as u32
+}
@@ -0,0 +1,330 @@
+#[cfg(feature = "text_image")]
+use std::fmt;
+use std::{
+    error::Error,
+    io::{self, Write},
+};
+
+use crate::{
+    color::{ANSIColor, ANSI_ESCAPE_CLOSE},
+    FragmentInfo, FragmentWriter,
+};
+
+/// A styling command, the backends queue it into the output in their own way.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum Command<'a> {
+    /// Set the foreground color.
+    SetForeground(&'a ANSIColor),
+    /// Set the background color.
+    SetBackground(&'a ANSIColor),
+    /// Swap the foreground and the background colors (the reverse video).
+    SetReverse,
+    /// Reset the colors and the attributes to the defaults.
+    Reset,
+    /// Print the text with the current style.
+    Print(&'a str),
+    /// Move the cursor to the column and the row, from `0`.
+    MoveTo(u16, u16),
+    /// Clear the whole screen.
+    ClearScreen,
+}
+
+/// A sink for the styling commands of a specific terminal library, or any other output that has its own styling
+/// (e.g. a log sink that uses a markup).
+///
+/// The transparent colors are never passed to the backend.
+///
+/// ```
+/// # use aarty::{style::{Command, StyleBackend, StyleWriter}, convert_image_to_ascii, Config, PixelImage, Rgba, Sympols, COLORS};
+/// # use std::io::{self, Write};
+/// /// Write the colors as tags.
+/// struct Markup;
+///
+/// impl StyleBackend for Markup {
+///     fn queue(&self, out: &mut dyn Write, command: Command<'_>) -> io::Result<()> {
+///         match command {
+///             Command::SetForeground(color) => {
+///                 let (r, g, b) = color.rgb().unwrap();
+///                 write!(out, "<fg #{r:02x}{g:02x}{b:02x}>")
+///             }
+///             Command::SetBackground(color) => {
+///                 let (r, g, b) = color.rgb().unwrap();
+///                 write!(out, "<bg #{r:02x}{g:02x}{b:02x}>")
+///             }
+///             Command::Reset => out.write_all(b"</>"),
+///             Command::Print(text) => out.write_all(text.as_bytes()),
+///             _ => Ok(()),
+///         }
+///     }
+/// }
+///
+/// struct Red;
+///
+/// impl PixelImage for Red {
+///     fn dimensions(&self) -> (u32, u32) {
+///         (2, 1)
+///     }
+///
+///     fn get_pixel(&self, _: u32, _: u32) -> Rgba {
+///         Rgba { r: 255, g: 0, b: 0, a: 255 }
+///     }
+/// }
+///
+/// let config = Config::new(Sympols::new(vec!['#'])).with_flags(COLORS);
+/// let mut out = StyleWriter::new(Vec::new(), Markup);
+/// convert_image_to_ascii(&config, &Red, &mut out).unwrap();
+/// assert_eq!(String::from_utf8(out.into_inner()).unwrap(), "<fg #ff0000>#</><fg #ff0000>#</>\n");
+/// ```
+pub trait StyleBackend {
+    /// Queue a command into the output, the caller flushes it.
+    fn queue(&self, out: &mut dyn Write, command: Command<'_>) -> io::Result<()>;
+}
+
+impl<B: StyleBackend + ?Sized> StyleBackend for &B {
+    #[inline]
+    fn queue(&self, out: &mut dyn Write, command: Command<'_>) -> io::Result<()> {
+        (**self).queue(out, command)
+    }
+}
+
+/// The ANSI escape codes, the default backend.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ansi;
+
+impl StyleBackend for Ansi {
+    #[inline]
+    fn queue(&self, out: &mut dyn Write, command: Command<'_>) -> io::Result<()> {
+        match command {
+            Command::SetForeground(color) => write!(out, "{color}"),
+            Command::SetBackground(color) => write!(out, "{color:-}"),
+            Command::SetReverse => out.write_all(b"\x1b[7m"),
+            Command::Reset => out.write_all(ANSI_ESCAPE_CLOSE.as_bytes()),
+            Command::Print(text) => out.write_all(text.as_bytes()),
+            Command::MoveTo(x, y) => write!(out, "\x1b[{};{}H", y + 1, x + 1),
+            Command::ClearScreen => out.write_all(b"\x1b[2J"),
+        }
+    }
+}
+
+/// The [`crossterm`] commands, they're queued with crossterm itself,
+/// so it also works with the legacy Windows console (when the output is the console).
+///
+/// ```
+/// # use aarty::{style::{Ansi, Command, Crossterm, StyleBackend}, ANSIColor};
+/// let red = ANSIColor::new(255, 0, 0);
+/// let mut out = Vec::new();
+/// for command in [Command::MoveTo(0, 1), Command::SetForeground(&red), Command::Print("#"), Command::Reset] {
+///     Crossterm.queue(&mut out, command).unwrap();
+/// }
+/// assert_eq!(out, b"\x1b[2;1H\x1b[38;2;255;0;0m#\x1b[0m");
+/// ```
+#[cfg(feature = "crossterm")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Crossterm;
+
+#[cfg(feature = "crossterm")]
+impl StyleBackend for Crossterm {
+    fn queue(&self, out: &mut dyn Write, command: Command<'_>) -> io::Result<()> {
+        use crossterm::{cursor, style, terminal, QueueableCommand};
+
+        match command {
+            Command::SetForeground(color) => out.queue(style::SetForegroundColor(color.into())),
+            Command::SetBackground(color) => out.queue(style::SetBackgroundColor(color.into())),
+            Command::SetReverse => out.queue(style::SetAttribute(style::Attribute::Reverse)),
+            Command::Reset => out.queue(style::ResetColor),
+            Command::Print(text) => out.queue(style::Print(text)),
+            Command::MoveTo(x, y) => out.queue(cursor::MoveTo(x, y)),
+            Command::ClearScreen => out.queue(terminal::Clear(terminal::ClearType::All)),
+        }?;
+        Ok(())
+    }
+}
+
+/// The [`termion`] escape codes, only on unix since [`termion`] doesn't build on the other platforms.
+///
+/// ```
+/// # use aarty::{style::{Command, StyleBackend, Termion}, ANSIColor};
+/// let mut out = Vec::new();
+/// for command in [Command::SetBackground(&ANSIColor::indexed(42)), Command::Print("#"), Command::Reset] {
+///     Termion.queue(&mut out, command).unwrap();
+/// }
+/// assert_eq!(out, b"\x1b[48;5;42m#\x1b[m");
+/// ```
+#[cfg(all(unix, feature = "termion"))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Termion;
+
+#[cfg(all(unix, feature = "termion"))]
+impl StyleBackend for Termion {
+    fn queue(&self, out: &mut dyn Write, command: Command<'_>) -> io::Result<()> {
+        use termion::{clear, color, cursor, style};
+
+        /// Write the color as the foreground (or the background).
+        fn layer(out: &mut dyn Write, color: &ANSIColor, fg: bool) -> io::Result<()> {
+            fn write<C: color::Color>(out: &mut dyn Write, color: C, fg: bool) -> io::Result<()> {
+                if fg {
+                    write!(out, "{}", color::Fg(color))
+                } else {
+                    write!(out, "{}", color::Bg(color))
+                }
+            }
+
+            if let Some(idx) = color.index() {
+                write(out, color::AnsiValue(idx), fg)
+            } else if let Some((r, g, b)) = color.rgb() {
+                write(out, color::Rgb(r, g, b), fg)
+            } else {
+                write(out, color::Reset, fg)
+            }
+        }
+
+        match command {
+            Command::SetForeground(color) => layer(out, color, true),
+            Command::SetBackground(color) => layer(out, color, false),
+            Command::SetReverse => write!(out, "{}", style::Invert),
+            Command::Reset => write!(out, "{}", style::Reset),
+            Command::Print(text) => out.write_all(text.as_bytes()),
+            Command::MoveTo(x, y) => write!(out, "{}", cursor::Goto(x + 1, y + 1)),
+            Command::ClearScreen => write!(out, "{}", clear::All),
+        }
+    }
+}
+
+/// The transparent color is the terminal default color.
+///
+/// ```
+/// # use aarty::ANSIColor;
+/// # use crossterm::style::Color;
+/// assert_eq!(Color::from(&ANSIColor::new(1, 2, 3)), Color::Rgb { r: 1, g: 2, b: 3 });
+/// assert_eq!(Color::from(&ANSIColor::indexed(42)), Color::AnsiValue(42));
+/// ```
+#[cfg(feature = "crossterm")]
+impl From<&ANSIColor> for crossterm::style::Color {
+    fn from(color: &ANSIColor) -> Self {
+        if let Some(idx) = color.index() {
+            crossterm::style::Color::AnsiValue(idx)
+        } else if let Some((r, g, b)) = color.rgb() {
+            crossterm::style::Color::Rgb { r, g, b }
+        } else {
+            crossterm::style::Color::Reset
+        }
+    }
+}
+
+/// A [`FragmentWriter`] that writes the colors with a [`StyleBackend`].
+///
+/// Any [`Write`] is a [`FragmentWriter`] that uses the [`Ansi`] backend, this wrapper is only needed for the other backends.
+#[derive(Debug)]
+pub struct StyleWriter<W, B> {
+    out: W,
+    backend: B,
+}
+
+impl<W: Write, B: StyleBackend> StyleWriter<W, B> {
+    /// Construct a new instance.
+    #[inline]
+    pub fn new(out: W, backend: B) -> Self {
+        Self { out, backend }
+    }
+
+    /// Return the inner writer.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl<W: Write, B: StyleBackend> FragmentWriter for StyleWriter<W, B> {
+    #[inline]
+    fn background(&mut self, bc: &ANSIColor) -> Result<bool, Box<dyn Error>> {
+        write_background(&mut self.out, &self.backend, bc)
+    }
+
+    #[inline]
+    fn write_fragment(&mut self, info: FragmentInfo) -> Result<(), Box<dyn Error>> {
+        self.out.write_fragment(info)
+    }
+
+    #[inline]
+    fn write_colored_fragment(
+        &mut self,
+        info: FragmentInfo,
+        bc: Option<&ANSIColor>,
+        fc: Option<&ANSIColor>,
+    ) -> Result<(), Box<dyn Error>> {
+        write_colored_fragment(&mut self.out, &self.backend, info, bc, fc)
+    }
+
+    #[inline]
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Box<dyn Error>> {
+        self.out.write_all(bytes)?;
+        Ok(())
+    }
+}
+
+/// Set the whole image background.
+pub(crate) fn write_background<W, B>(
+    out: &mut W,
+    backend: &B,
+    bc: &ANSIColor,
+) -> Result<bool, Box<dyn Error>>
+where
+    W: Write,
+    B: StyleBackend + ?Sized,
+{
+    if !bc.is_transparent() {
+        backend.queue(out, Command::SetBackground(bc))?;
+    }
+    Ok(true)
+}
+
+/// Write a fragment with its colors, then reset each color that was set.
+pub(crate) fn write_colored_fragment<W, B>(
+    out: &mut W,
+    backend: &B,
+    info: FragmentInfo,
+    bc: Option<&ANSIColor>,
+    fc: Option<&ANSIColor>,
+) -> Result<(), Box<dyn Error>>
+where
+    W: Write,
+    B: StyleBackend + ?Sized,
+{
+    if let Some(bc) = bc.filter(|c| !c.is_transparent()) {
+        backend.queue(out, Command::SetBackground(bc))?;
+    }
+    if let Some(fc) = fc.filter(|c| !c.is_transparent()) {
+        backend.queue(out, Command::SetForeground(fc))?;
+    }
+    backend.queue(out, Command::Print(info.sym.encode_utf8(&mut [0; 4])))?;
+    // Every color needs its own reset, so the output is the same as before the backends.
+    if bc.is_some() {
+        backend.queue(out, Command::Reset)?;
+    }
+    if fc.is_some() {
+        backend.queue(out, Command::Reset)?;
+    }
+    Ok(())
+}
+
+/// An [`io::Write`] over a [`fmt::Write`], so the backends can write into a formatter (e.g. in a [`fmt::Display`]).
+#[cfg(feature = "text_image")]
+pub(crate) struct FmtWriter<'a>(pub(crate) &'a mut dyn fmt::Write);
+
+#[cfg(feature = "text_image")]
+impl Write for FmtWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let s =
+            std::str::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.0
+            .write_str(s)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "formatter error"))?;
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -6,6 +6,75 @@ pub const EMPTY_SET: Sympols = Sympols::new(vec![]);
 /// The default empty character(a.k.a. sympol). Reterund by [`Sympols::get`] when the sympols is [`EMPTY_SET`].
 pub const EMPTY_CHAR: char = ' ';
 
+// The presets go from the least ink to the most, so the darker pixels get the emptier sympols
+// (the first sympol maps the darker pixels, like any other sympols set).
+// The ink of a character depends on the font, so the order of the text presets is approximate,
+// only the blocks, the dots and the moons are exact.
+
+/// The binary default set.
+pub const DEFAULT: &[char] = &[
+    ' ', '.', ',', '-', '~', '!', ';', ':', '=', '*', '&', '%', '$', '@', '#',
+];
+/// The standard 10 levels ramp.
+pub const STANDARD: &[char] = &[' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+/// The 70 levels ramp by [Paul Bourke](https://paulbourke.net/dataformats/asciiart/).
+pub const BOURKE: &[char] = &[
+    ' ', '.', '\'', '`', '^', '"', ',', ':', ';', 'I', 'l', '!', 'i', '>', '<', '~', '+', '_', '-',
+    '?', ']', '[', '}', '{', '1', ')', '(', '|', '\\', '/', 't', 'f', 'j', 'r', 'x', 'n', 'u', 'v',
+    'c', 'z', 'X', 'Y', 'U', 'J', 'C', 'L', 'Q', '0', 'O', 'Z', 'm', 'w', 'q', 'p', 'd', 'b', 'k',
+    'h', 'a', 'o', '*', '#', 'M', 'W', '&', '8', '%', 'B', '@', '$',
+];
+/// The shade blocks, with none, a quarter, a half, three quarters and all of the cell inked.
+pub const BLOCKS: &[char] = &[' ', '░', '▒', '▓', '█'];
+/// Braille patterns, from zero to eight dots.
+///
+/// ```
+/// # use aarty::sympols::DOTS;
+/// // The Braille patterns bits are their dots.
+/// let dots: Vec<_> = DOTS[1..].iter().map(|c| (*c as u32 - 0x2800).count_ones()).collect();
+/// assert_eq!(dots, (1..=8).collect::<Vec<_>>());
+/// ```
+pub const DOTS: &[char] = &[' ', '⠁', '⠃', '⠇', '⡇', '⣇', '⣧', '⣷', '⣿'];
+/// The digits, roughly from the least ink to the most (it depends on the font).
+pub const DIGITS: &[char] = &[' ', '1', '7', '3', '2', '4', '5', '6', '9', '0', '8'];
+/// Halfwidth Katakana, roughly from the simplest shapes to the busiest (it depends on the font).
+pub const KATAKANA: &[char] = &[
+    ' ', '･', 'ｰ', 'ﾉ', 'ﾍ', 'ｿ', 'ｼ', 'ﾂ', 'ﾐ', 'ｴ', 'ﾆ', 'ﾓ', 'ﾀ', 'ﾎ', 'ﾑ', 'ﾏ',
+];
+/// The moon phases emojis, from the new moon to the full moon, they're two columns wide.
+///
+/// ```
+/// # use aarty::sympols::MOONS;
+/// // The phases are consecutive, the lit part grows with every one.
+/// let phases: Vec<_> = MOONS.iter().map(|c| *c as u32).collect();
+/// assert_eq!(phases, (0x1F311..=0x1F315).collect::<Vec<_>>());
+/// ```
+pub const MOONS: &[char] = &['🌑', '🌒', '🌓', '🌔', '🌕'];
+
+/// The presets names, See: [`Sympols::preset`].
+///
+/// Every preset maps the darker pixels to its first sympol (the blank), and the lighter ones to its last sympol.
+/// The text presets are only roughly ordered by their ink, since it depends on the font:
+/// ```
+/// # use aarty::sympols::{MOONS, PRESETS};
+/// for (name, set) in PRESETS {
+///     assert!(set.iter().enumerate().all(|(i, c)| !set[i + 1..].contains(c)), "{name} has duplicates");
+///     if *set != MOONS {
+///         assert_eq!(set[0], ' ', "{name} should start with the blank");
+///     }
+/// }
+/// ```
+pub const PRESETS: &[(&str, &[char])] = &[
+    ("default", DEFAULT),
+    ("standard", STANDARD),
+    ("bourke", BOURKE),
+    ("blocks", BLOCKS),
+    ("dots", DOTS),
+    ("digits", DIGITS),
+    ("katakana", KATAKANA),
+    ("moons", MOONS),
+];
+
 /// The sympols (characters) that we will use to represent our pixels based on their color.
 #[derive(Debug, PartialEq, PartialOrd, Clone, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -19,6 +88,20 @@ impl Sympols {
         Sympols { set }
     }
 
+    /// Construct a sympols set from a named preset, See: [`PRESETS`].
+    ///
+    /// ```
+    /// # use aarty::{sympols, Sympols};
+    /// assert_eq!(Sympols::preset("blocks"), Some(sympols::BLOCKS.into()));
+    /// assert_eq!(Sympols::preset("nope"), None);
+    /// ```
+    pub fn preset(name: &str) -> Option<Sympols> {
+        PRESETS
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, set)| (*set).into())
+    }
+
     /// Construct a new empty set.
     pub const fn empty() -> Self {
         EMPTY_SET
@@ -45,6 +128,16 @@ impl Sympols {
         self.set.is_empty()
     }
 
+    /// The widest symbol display width, in terminal columns.
+    #[cfg(feature = "text_image")]
+    #[inline]
+    pub(crate) fn max_width(&self) -> usize {
+        match self.set.iter().map(|sym| width(*sym)).max() {
+            Some(width) => width,
+            None => width(EMPTY_CHAR),
+        }
+    }
+
     /// Calculate the index of the sympol in the set based on the [`RGBA`] value.
     #[inline]
     pub(crate) fn sym_index(&self, pixel: &Rgba) -> usize {
@@ -52,17 +145,9 @@ impl Sympols {
             return 0;
         }
         let len = self.len();
-        // FIXME: handle the alpha channel
+        // The alpha is composited before (see `Config::alpha`).
         let mut idx = (pixel.r as usize + pixel.g as usize + pixel.b as usize) / 3;
 
-        if idx == 0 {
-            return 0;
-        }
-
-        if pixel.a < 120 {
-            idx = pixel.a as usize % idx;
-        }
-
         // I'll kill my self if this didn't work.
         idx /= 255 / len;
         if idx >= len {
@@ -90,3 +175,10 @@ impl From<Vec<char>> for Sympols {
         Self::new(value)
     }
 }
+
+/// The symbol display width, in terminal columns (the emojis and the wide CJK characters take two).
+#[cfg(feature = "text_image")]
+#[inline]
+pub(crate) fn width(sym: char) -> usize {
+    unicode_width::UnicodeWidthChar::width(sym).unwrap_or(0)
+}
@@ -0,0 +1,351 @@
+use std::{env, fmt::Display, str::FromStr};
+#[cfg(unix)]
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    os::unix::io::AsRawFd,
+    time::{Duration, Instant},
+};
+
+/// The default terminal cell aspect ratio (height / width), most of the fonts are about twice taller than their width.
+pub const CELL_ASPECT: f32 = 2.0;
+
+/// Query the terminal size in columns and rows.
+///
+/// It asks the terminal driver (`TIOCGWINSZ`) about the size of the terminal that attached to the stdout, stderr or stdin
+/// (in this order), and falls back to the `COLUMNS` and `LINES` environment variables.
+pub fn terminal_size() -> Option<(u16, u16)> {
+    #[cfg(unix)]
+    for fd in [libc::STDOUT_FILENO, libc::STDERR_FILENO, libc::STDIN_FILENO] {
+        if let Some(size) = ioctl_size(fd) {
+            return Some(size);
+        }
+    }
+    env_size()
+}
+
+/// Query the terminal cell size in pixels, if the terminal reports its size in pixels.
+pub fn cell_size() -> Option<(u16, u16)> {
+    #[cfg(unix)]
+    for fd in [libc::STDOUT_FILENO, libc::STDERR_FILENO, libc::STDIN_FILENO] {
+        if let Some(ws) = winsize(fd) {
+            if ws.ws_xpixel > 0 && ws.ws_ypixel > 0 {
+                return Some((ws.ws_xpixel / ws.ws_col, ws.ws_ypixel / ws.ws_row));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(unix)]
+fn ioctl_size(fd: libc::c_int) -> Option<(u16, u16)> {
+    winsize(fd).map(|ws| (ws.ws_col, ws.ws_row))
+}
+
+#[cfg(unix)]
+fn winsize(fd: libc::c_int) -> Option<libc::winsize> {
+    let mut ws = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // SAFETY: `TIOCGWINSZ` only writes into the provided `winsize`.
+    let res = unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &mut ws) };
+    if res == 0 && ws.ws_col > 0 && ws.ws_row > 0 {
+        return Some(ws);
+    }
+    None
+}
+
+/// The controlling terminal (`/dev/tty`), it can be queried about its capabilities.
+///
+/// See: [`crate::capabilities::Capabilities::probe`]
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct Tty {
+    file: File,
+}
+
+#[cfg(unix)]
+impl Tty {
+    /// Open the controlling terminal.
+    pub fn open() -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+        Ok(Self { file })
+    }
+}
+
+/// How long to wait for the late answers after a terminal query timed out.
+#[cfg(unix)]
+const DRAIN_GRACE: Duration = Duration::from_millis(50);
+
+/// Wait until the fd has something to read, return false if the timeout passed first.
+#[cfg(unix)]
+fn wait_readable(fd: libc::c_int, timeout: Duration) -> io::Result<bool> {
+    loop {
+        let mut pfd = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: we pass exactly one valid `pollfd`.
+        let ready = unsafe { libc::poll(&mut pfd, 1, timeout.as_millis().max(1) as libc::c_int) };
+        if ready >= 0 {
+            return Ok(ready > 0);
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+}
+
+/// Wait (up to the timeout) for the standard input to have something to read.
+///
+/// See: [`crate::viewer::Keys::with_pending`]
+#[cfg(unix)]
+#[inline]
+pub fn input_pending(timeout: Duration) -> io::Result<bool> {
+    wait_readable(libc::STDIN_FILENO, timeout)
+}
+
+#[cfg(unix)]
+impl crate::capabilities::TerminalQuery for Tty {
+    fn query(&mut self, request: &[u8], timeout: Duration) -> io::Result<Vec<u8>> {
+        let fd = self.file.as_raw_fd();
+        let _raw = RawMode::enable(fd)?;
+
+        self.file.write_all(request)?;
+        self.file.flush()?;
+
+        let deadline = Instant::now() + timeout;
+        let mut response = Vec::new();
+        let mut buf = [0; 256];
+        let mut answered = false;
+        while !answered {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() || !wait_readable(fd, remaining)? {
+                break;
+            }
+            let n = self.file.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            response.extend_from_slice(&buf[..n]);
+            // The device attributes answer is the last one.
+            if let Some(start) = response.windows(3).rposition(|w| w == b"\x1b[?") {
+                answered = response[start..].contains(&b'c');
+            }
+        }
+
+        if !answered {
+            // A slow terminal might still answer, so swallow the late answers instead of leaving them
+            // in the input (where the shell or the next program would read them).
+            while wait_readable(fd, DRAIN_GRACE)? {
+                if self.file.read(&mut buf)? == 0 {
+                    break;
+                }
+            }
+            // SAFETY: `tcflush` only discards the pending input of a valid fd.
+            unsafe { libc::tcflush(fd, libc::TCIFLUSH) };
+        }
+
+        if response.is_empty() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        Ok(response)
+    }
+}
+
+/// Put the terminal in raw mode (no echo, no line buffering), and restore the original mode when dropped.
+#[cfg(unix)]
+struct RawMode {
+    fd: libc::c_int,
+    original: libc::termios,
+}
+
+#[cfg(unix)]
+impl RawMode {
+    /// Non-blocking raw mode, the reads return immediately.
+    fn enable(fd: libc::c_int) -> io::Result<Self> {
+        Self::with(fd, |raw| {
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+            raw.c_cc[libc::VMIN] = 0;
+            raw.c_cc[libc::VTIME] = 0;
+        })
+    }
+
+    /// Blocking raw mode without the signal keys, so ctrl+c is read as a key.
+    fn keys(fd: libc::c_int) -> io::Result<Self> {
+        Self::with(fd, |raw| {
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG | libc::IEXTEN);
+            raw.c_iflag &= !(libc::IXON | libc::ICRNL);
+            raw.c_cc[libc::VMIN] = 1;
+            raw.c_cc[libc::VTIME] = 0;
+        })
+    }
+
+    fn with(fd: libc::c_int, f: impl FnOnce(&mut libc::termios)) -> io::Result<Self> {
+        // SAFETY: `tcgetattr` initializes the termios struct on success, and we check for that.
+        let original = unsafe {
+            let mut termios = std::mem::zeroed::<libc::termios>();
+            if libc::tcgetattr(fd, &mut termios) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            termios
+        };
+        let mut raw = original;
+        f(&mut raw);
+        // SAFETY: `raw` is a valid termios struct.
+        if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self { fd, original })
+    }
+}
+
+#[cfg(unix)]
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        // SAFETY: restoring the termios struct that we got from `tcgetattr`.
+        unsafe {
+            libc::tcsetattr(self.fd, libc::TCSANOW, &self.original);
+        }
+    }
+}
+
+/// Switch to the alternate screen and read the keys in raw mode, the terminal is restored when it's dropped.
+///
+/// It writes to the inner writer, so it can be used as the viewer output, See: [`crate::viewer::Viewer::run`].
+#[cfg(unix)]
+pub struct AlternateScreen<W: Write> {
+    out: W,
+    _raw: RawMode,
+}
+
+#[cfg(unix)]
+impl<W: Write> AlternateScreen<W> {
+    /// Enter the alternate screen, hide the cursor and put the standard input in raw mode.
+    pub fn enter(mut out: W) -> io::Result<Self> {
+        let raw = RawMode::keys(libc::STDIN_FILENO)?;
+        out.write_all(b"\x1b[?1049h\x1b[?25l")?;
+        out.flush()?;
+        Ok(Self { out, _raw: raw })
+    }
+}
+
+#[cfg(unix)]
+impl<W: Write> Write for AlternateScreen<W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.out.write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+#[cfg(unix)]
+impl<W: Write> Drop for AlternateScreen<W> {
+    fn drop(&mut self) {
+        // Show the cursor and leave the alternate screen, the raw mode is restored after that.
+        let _ = self.out.write_all(b"\x1b[?25h\x1b[?1049l");
+        let _ = self.out.flush();
+    }
+}
+
+#[cfg(unix)]
+impl<W: Write> std::fmt::Debug for AlternateScreen<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlternateScreen").finish_non_exhaustive()
+    }
+}
+
+fn env_size() -> Option<(u16, u16)> {
+    let var = |name| {
+        env::var(name)
+            .ok()?
+            .trim()
+            .parse::<u16>()
+            .ok()
+            .filter(|v| *v > 0)
+    };
+    Some((var("COLUMNS")?, var("LINES")?))
+}
+
+/// How to fit an image into an area.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Fit {
+    /// Fill the area width, the height may overflow.
+    Width,
+    /// Fill the area height, the width may overflow.
+    Height,
+    /// Make the whole image visible inside the area.
+    #[default]
+    Contain,
+    /// Fill the whole area, one of the dimensions may overflow.
+    Cover,
+}
+
+impl Fit {
+    /// Calculate the result size (in cells) of fitting an image with the size `image` (in pixels) into `area` (in cells),
+    /// preserving the image aspect ratio, considering that each cell is `cell_aspect` times taller than its width.
+    ///
+    /// ```
+    /// # use aarty::terminal::{Fit, CELL_ASPECT};
+    /// // A landscape image fits the terminal width.
+    /// assert_eq!(Fit::Contain.fit((400, 200), (80, 24), CELL_ASPECT), (80, 20));
+    /// assert_eq!(Fit::Cover.fit((400, 200), (80, 24), CELL_ASPECT), (96, 24));
+    /// // While a portrait one fits its height.
+    /// assert_eq!(Fit::Contain.fit((200, 400), (80, 24), CELL_ASPECT), (24, 24));
+    /// assert_eq!(Fit::Width.fit((200, 400), (80, 24), CELL_ASPECT), (80, 80));
+    /// ```
+    pub fn fit(self, image: (u32, u32), area: (u32, u32), cell_aspect: f32) -> (u32, u32) {
+        let (w, h) = (image.0.max(1) as f32, image.1.max(1) as f32 / cell_aspect);
+        let (cols, rows) = (area.0 as f32, area.1 as f32);
+        let by_width = cols / w;
+        let by_height = rows / h;
+        let ratio = match self {
+            Fit::Width => by_width,
+            Fit::Height => by_height,
+            Fit::Contain => by_width.min(by_height),
+            Fit::Cover => by_width.max(by_height),
+        };
+        (
+            ((w * ratio).round() as u32).max(1),
+            ((h * ratio).round() as u32).max(1),
+        )
+    }
+}
+
+impl FromStr for Fit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "width" | "w" => Ok(Fit::Width),
+            "height" | "h" => Ok(Fit::Height),
+            "contain" => Ok(Fit::Contain),
+            "cover" => Ok(Fit::Cover),
+            other => Err(format!(
+                "unknown fit mode `{other}`, expected one of width, height, contain or cover"
+            )),
+        }
+    }
+}
+
+impl Display for Fit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Fit::Width => "width",
+            Fit::Height => "height",
+            Fit::Contain => "contain",
+            Fit::Cover => "cover",
+        })
+    }
+}
@@ -1,14 +1,22 @@
 use core::fmt;
-use std::fmt::Display;
+use std::{error::Error, fmt::Display};
 
-use crate::{Config, FragmentInfo, FragmentWriter, PixelImage};
+use crate::{sympols, Config, FragmentInfo, FragmentItem, FragmentWriter, PixelImage};
 
+/// Erase from the cursor to the end of the line.
+const ERASE_LINE_END: &str = "\u{001b}[K";
+
+#[cfg(feature = "colors")]
+use crate::{
+    color::ANSIColor,
+    style::{Ansi, Command, FmtWriter, StyleBackend},
+};
 #[cfg(feature = "colors")]
-use crate::color::{ANSIColor, ANSI_ESCAPE_CLOSE};
+use std::io;
 
 /// Trait to convert an imgae to ASCII art.
 pub trait ToTextImage {
-    /// constructs a [`TextImage`] instance and use it with [`crate::convert_image_to_ascii`] and return it.
+    /// constructs a [`TextImage`] instance from the image [`crate::fragments`] and return it.
     fn to_text(&self, cfg: Config) -> TextImage;
 }
 
@@ -20,7 +28,11 @@ where
     fn to_text(&self, cfg: Config) -> TextImage {
         let (w, h) = self.dimensions();
         let mut buf = TextImage::new(cfg.clone(), w, h);
-        crate::convert_image_to_ascii(&cfg, self, &mut buf).expect("Unretchable");
+        buf.fragments.extend(
+            crate::fragments(&cfg, self)
+                .filter_map(FragmentItem::fragment)
+                .map(IndexdFragment::from),
+        );
         buf
     }
 }
@@ -227,6 +239,157 @@ impl TextImage {
         self.len() == 0
     }
 
+    /// The rows number.
+    #[inline]
+    pub fn rows(&self) -> usize {
+        if self.row_len == 0 {
+            return 0;
+        }
+        (self.len() + self.row_len - 1) / self.row_len
+    }
+
+    /// The rows display width, in terminal columns, enough for a row of the widest symbol in the set.
+    ///
+    /// ```
+    /// # use aarty::{Config, Sympols, TextImage};
+    /// assert_eq!(TextImage::new(Config::new(Sympols::new(vec!['.', '#'])), 4, 1).width(), 4);
+    /// // The moons are two columns wide.
+    /// assert_eq!(TextImage::new(Config::new(Sympols::new(vec!['.', '🌕'])), 4, 1).width(), 8);
+    /// ```
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.row_len * self.config.sympols.max_width()
+    }
+
+    /// Write only the fragments that differ from `prev` to the [`FragmentWriter`].
+    ///
+    /// Each run of changed fragments in a row is preceded by a cursor positioning escape (`CSI row;col H`),
+    /// so if `prev` is what currently on the screen (drawn from the top left corner), the writer will end up
+    /// displaying this image without redrawing the unchanged parts.
+    /// The cursor columns are the symbols display width, so the wide symbols (e.g. [`crate::sympols::MOONS`]) work too.
+    ///
+    /// If the config differs (e.g. the colors mode or the background), the whole image is redrawn.
+    ///
+    /// ```
+    /// # use aarty::{Config, IndexdFragment, Sympols, TextImage};
+    /// let frame = |indices: [u8; 3]| {
+    ///     let mut image = TextImage::new(Config::new(Sympols::new(vec!['🌑', '.'])), 3, 1);
+    ///     for (idx, sym_index) in indices.into_iter().enumerate() {
+    ///         image.insert(idx, IndexdFragment::new(sym_index));
+    ///     }
+    ///     image
+    /// };
+    ///
+    /// // The moon is two columns wide, so the last one moves to the left (and the rest of the row is erased).
+    /// let mut out = Vec::new();
+    /// frame([0, 1, 0]).diff(&frame([0, 0, 0]), &mut out).unwrap();
+    /// assert_eq!(String::from_utf8(out).unwrap(), "\x1b[1;3H.🌑\x1b[K");
+    /// ```
+    ///
+    /// Return an error if the two images doesn't have the same size.
+    ///
+    /// ```no_run
+    /// # use aarty::*;
+    /// # use std::io::{self, Write};
+    /// let cfg = Config::new(vec![' ', '.', '*', '#'].into()).with_flags(COLORS);
+    ///
+    /// let prev = image::open("frame0.png").unwrap().to_text(cfg.clone());
+    /// let next = image::open("frame1.png").unwrap().to_text(cfg);
+    ///
+    /// let mut out = io::stdout().lock();
+    /// print!("\x1b[H{prev}");
+    /// next.diff(&prev, &mut out).expect("IO error");
+    /// out.flush().unwrap();
+    /// ```
+    pub fn diff<W>(&self, prev: &TextImage, out: &mut W) -> Result<(), Box<dyn Error>>
+    where
+        W: FragmentWriter,
+    {
+        if self.row_len != prev.row_len || self.len() != prev.len() {
+            return Err(format!(
+                "can't diff a {}x{} image against a {}x{} one",
+                self.row_len,
+                self.rows(),
+                prev.row_len,
+                prev.rows()
+            )
+            .into());
+        }
+
+        // A different config (e.g. the colors mode or the background) changes every cell.
+        let same_config = self.config == prev.config;
+        let mut in_run = false;
+        // The display columns of the current fragment, on the screen and in this image.
+        let (mut old_col, mut col) = (0, 0);
+        for (idx, (frag, old)) in self.fragments.iter().zip(&prev.fragments).enumerate() {
+            if idx % self.row_len == 0 {
+                if old_col > col {
+                    // This row got narrower, erase the rest of the old one.
+                    out.write_bytes(ERASE_LINE_END.as_bytes())?;
+                }
+                in_run = false;
+                (old_col, col) = (0, 0);
+            }
+            let sym = self.config.sympols.get(frag.sym_index as usize);
+            let old_sym = prev.config.sympols.get(old.sym_index as usize);
+            // Once the columns are shifted (by a wider or a narrower symbol), the rest of the row is redrawn.
+            let unchanged = same_config && col == old_col && sym == old_sym && {
+                #[cfg(feature = "colors")]
+                {
+                    frag.fg == old.fg
+                }
+                #[cfg(not(feature = "colors"))]
+                true
+            };
+            old_col += sympols::width(old_sym);
+            if unchanged {
+                col = old_col;
+                in_run = false;
+                continue;
+            }
+            if !in_run {
+                out.write_bytes(
+                    format!("\u{001b}[{};{}H", idx / self.row_len + 1, col + 1).as_bytes(),
+                )?;
+                in_run = true;
+            }
+            col += sympols::width(sym);
+            self._write_fragment(frag, sym, out)?;
+        }
+        if old_col > col {
+            out.write_bytes(ERASE_LINE_END.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn _write_fragment<W>(
+        &self,
+        frag: &IndexdFragment,
+        sym: char,
+        out: &mut W,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        W: FragmentWriter,
+    {
+        let info = FragmentInfo {
+            sym,
+            sym_index: frag.sym_index as usize,
+            #[cfg(feature = "colors")]
+            fg: frag.fg.clone(),
+        };
+
+        #[cfg(feature = "colors")]
+        if self.config.use_colors() {
+            let (bc, fg) = self
+                .config
+                .cell_style(&frag.fg, self.config.background_color());
+            return out.write_colored_fragment(info, bc.as_ref(), fg.as_ref());
+        }
+
+        out.write_fragment(info)
+    }
+
     #[inline(always)]
     fn _fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut i = 0;
@@ -244,67 +407,118 @@ impl TextImage {
 
 #[cfg(feature = "colors")]
 impl TextImage {
-    #[inline(always)]
-    fn _background(&self, f: &mut fmt::Formatter<'_>) -> std::result::Result<bool, fmt::Error> {
-        if let Some(bc) = &self.config.background {
-            write!(f, "{bc:-}")?;
-            return Ok(true);
+    /// Display the image with the colors written by the given backend, instead of the ANSI escape codes.
+    ///
+    /// ```
+    /// # use aarty::{style::Ansi, Config, IndexdFragment, Sympols, TextImage, ANSIColor, COLORS};
+    /// let mut image = TextImage::new(Config::new(Sympols::new(vec!['#'])).with_flags(COLORS), 1, 1);
+    /// image.insert(0, IndexdFragment::new_with_foreground(0, ANSIColor::new(255, 0, 0)));
+    /// assert_eq!(image.styled(&Ansi).to_string(), image.to_string());
+    /// ```
+    #[inline]
+    pub fn styled<'a, B: StyleBackend>(&'a self, backend: &'a B) -> Styled<'a, B> {
+        Styled {
+            image: self,
+            backend,
         }
-        Ok(false)
     }
 
-    #[inline]
-    fn _color_fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let has_background = {
-            if self.config.reversed() {
-                let mut r = false;
-                if let Some(bc) = &self.config.background {
-                    if !bc.is_transparent() {
-                        write!(f, "{bc}")?;
-                        r = true;
-                    }
-                }
-                r
+    /// Write the image with the colors queued into the given backend, See: [`StyleBackend`].
+    ///
+    /// ```
+    /// # use aarty::{style::Ansi, Config, IndexdFragment, Sympols, TextImage, ANSIColor, COLORS};
+    /// let mut image = TextImage::new(Config::new(Sympols::new(vec!['#'])).with_flags(COLORS), 1, 1);
+    /// image.insert(0, IndexdFragment::new_with_foreground(0, ANSIColor::new(255, 0, 0)));
+    /// let mut out = Vec::new();
+    /// image.write_styled(&mut out, &Ansi).unwrap();
+    /// assert_eq!(String::from_utf8(out).unwrap(), image.to_string());
+    /// ```
+    pub fn write_styled<W: io::Write, B: StyleBackend>(
+        &self,
+        out: &mut W,
+        backend: &B,
+    ) -> io::Result<()> {
+        if self.config.use_colors() {
+            self._color_write(out, backend)
+        } else {
+            write!(out, "{self}")
+        }
+    }
+
+    fn _color_write(&self, out: &mut dyn io::Write, backend: &dyn StyleBackend) -> io::Result<()> {
+        let background = self
+            .config
+            .background_color()
+            .filter(|bc| !bc.is_transparent());
+        let reversed = self.config.reversed() && !self.config.cell_colors();
+        if let Some(bc) = &background {
+            if reversed {
+                backend.queue(out, Command::SetForeground(bc))?;
             } else {
-                self._background(f)?
+                backend.queue(out, Command::SetBackground(bc))?;
             }
-        };
+        }
 
         let mut i = 0;
         for frag in &self.fragments {
             if i == self.row_len {
                 i = 0;
-                writeln!(f)?;
+                backend.queue(out, Command::Print("\n"))?;
             }
             i += 1;
 
-            if self.config.reversed() {
-                write!(f, "{c:-}", c = frag.fg)
-            } else {
-                write!(f, "{c}", c = frag.fg)
-            }?;
-
-            write!(
-                f,
-                "{ch}{ANSI_ESCAPE_CLOSE}",
-                ch = self.config.sympols.get(frag.sym_index as usize)
-            )?;
+            if !frag.fg.is_transparent() {
+                if self.config.cell_colors() {
+                    backend.queue(out, Command::SetBackground(&frag.fg))?;
+                    let fc = frag.fg.contrast().with_depth(self.config.color_depth);
+                    backend.queue(out, Command::SetForeground(&fc))?;
+                } else if reversed {
+                    backend.queue(out, Command::SetBackground(&frag.fg))?;
+                } else {
+                    backend.queue(out, Command::SetForeground(&frag.fg))?;
+                }
+            }
+            let sym = self.config.sympols.get(frag.sym_index as usize);
+            backend.queue(out, Command::Print(sym.encode_utf8(&mut [0; 4])))?;
+            backend.queue(out, Command::Reset)?;
         }
 
-        if has_background {
-            f.write_str(ANSI_ESCAPE_CLOSE)?;
+        if background.is_some() {
+            backend.queue(out, Command::Reset)?;
         }
 
         Ok(())
     }
 }
 
+/// Display a [`TextImage`] with a [`StyleBackend`], See: [`TextImage::styled`].
+#[cfg(feature = "colors")]
+#[derive(Debug)]
+pub struct Styled<'a, B> {
+    image: &'a TextImage,
+    backend: &'a B,
+}
+
+#[cfg(feature = "colors")]
+impl<B: StyleBackend> Display for Styled<'_, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.image.config.use_colors() {
+            self.image
+                ._color_write(&mut FmtWriter(f), self.backend)
+                .map_err(|_| fmt::Error)
+        } else {
+            self.image._fmt(f)
+        }
+    }
+}
+
 impl Display for TextImage {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         #[cfg(feature = "colors")]
         {
             if self.config.use_colors() {
-                self._color_fmt(f)
+                self._color_write(&mut FmtWriter(f), &Ansi)
+                    .map_err(|_| fmt::Error)
             } else {
                 self._fmt(f)
             }
@@ -0,0 +1,401 @@
+use std::{
+    io::{self, BufRead, ErrorKind, Read},
+    time::Duration,
+};
+
+use crate::{PixelImage, Rgba};
+
+const Y4M_MAGIC: &str = "YUV4MPEG2";
+const Y4M_FRAME: &str = "FRAME";
+/// The longest header line, a longer one is a broken stream (instead of buffering all of it).
+const MAX_HEADER_LEN: usize = 4096;
+
+/// A decoded video frame, stored as packed 8-bit RGB.
+#[derive(Debug, PartialEq, PartialOrd, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VideoFrame {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+impl VideoFrame {
+    /// Construct a new instance from packed RGB data.
+    ///
+    /// Return [`None`] if the data length doesn't match the dimensions.
+    pub fn from_rgb(width: u32, height: u32, data: Vec<u8>) -> Option<Self> {
+        if data.len() != width as usize * height as usize * 3 {
+            return None;
+        }
+        Some(Self {
+            width,
+            height,
+            data,
+        })
+    }
+
+    /// The packed RGB data.
+    #[inline(always)]
+    pub fn as_raw(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Return the packed RGB data.
+    #[inline(always)]
+    pub fn into_raw(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+impl PixelImage for VideoFrame {
+    #[inline(always)]
+    fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    #[inline]
+    fn get_pixel(&self, x: u32, y: u32) -> Rgba {
+        let i = (y as usize * self.width as usize + x as usize) * 3;
+        Rgba {
+            r: self.data[i],
+            g: self.data[i + 1],
+            b: self.data[i + 2],
+            a: u8::MAX,
+        }
+    }
+}
+
+/// The chroma subsampling of a YUV4MPEG2 stream.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Chroma {
+    /// 4:2:0, the chroma planes have half the width and half the height (`420jpeg`, `420paldv`, `420mpeg2`, `420`).
+    C420,
+    /// 4:2:2, the chroma planes have half the width.
+    C422,
+    /// 4:4:4, no subsampling.
+    C444,
+    /// Luma only.
+    Mono,
+}
+
+impl Chroma {
+    #[inline]
+    const fn plane_dimensions(self, w: usize, h: usize) -> (usize, usize) {
+        match self {
+            Chroma::C420 => ((w + 1) / 2, (h + 1) / 2),
+            Chroma::C422 => ((w + 1) / 2, h),
+            Chroma::C444 => (w, h),
+            Chroma::Mono => (0, 0),
+        }
+    }
+}
+
+/// A reader for YUV4MPEG2 (`.y4m`) streams, like the ones `ffmpeg -f yuv4mpegpipe` produces.
+///
+/// It's an iterator over the stream frames, converted to RGB.
+///
+/// ```
+/// # use aarty::{PixelImage, Rgba, video::Y4mReader};
+/// # use std::{io, time::Duration};
+/// // A 2x2 stream with two frames: a white and a black one.
+/// let mut stream = b"YUV4MPEG2 W2 H2 F25:1 Ip A1:1 C420jpeg XCOLORRANGE=FULL\n".to_vec();
+/// for luma in [255, 0] {
+///     stream.extend_from_slice(b"FRAME\n");
+///     stream.extend_from_slice(&[luma; 4]);
+///     stream.extend_from_slice(&[128, 128]);
+/// }
+///
+/// let reader = Y4mReader::new(stream.as_slice()).unwrap();
+/// assert_eq!(reader.dimensions(), (2, 2));
+/// assert_eq!(reader.frame_duration(), Duration::from_millis(40));
+///
+/// let frames = reader.collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(frames.len(), 2);
+/// assert_eq!(frames[0].get_pixel(1, 1), Rgba { r: 255, g: 255, b: 255, a: 255 });
+/// assert_eq!(frames[1].get_pixel(0, 0), Rgba { r: 0, g: 0, b: 0, a: 255 });
+///
+/// // The unknown tags are skipped, even the non ASCII ones.
+/// assert!(Y4mReader::new(b"YUV4MPEG2 W2 H2 \xc3\xa9t\xc3\xa9\n".as_slice()).is_ok());
+///
+/// // A header without an end isn't buffered forever.
+/// let endless = io::Read::chain(&b"YUV4MPEG2 W2 H2 X"[..], io::repeat(b'a'));
+/// assert!(Y4mReader::new(io::BufReader::new(endless)).is_err());
+/// ```
+#[derive(Debug)]
+pub struct Y4mReader<R> {
+    reader: R,
+    width: u32,
+    height: u32,
+    frame_rate: (u32, u32),
+    chroma: Chroma,
+    full_range: bool,
+    buf: Vec<u8>,
+}
+
+impl<R: BufRead> Y4mReader<R> {
+    /// Read the stream header and construct a new instance.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let header =
+            read_line(&mut reader)?.ok_or_else(|| invalid_data("empty YUV4MPEG2 stream"))?;
+        let mut params = header.split(' ');
+        if params.next() != Some(Y4M_MAGIC) {
+            return Err(invalid_data("not a YUV4MPEG2 stream"));
+        }
+
+        let mut width = None;
+        let mut height = None;
+        let mut frame_rate = (25, 1);
+        let mut chroma = Chroma::C420;
+        let mut full_range = false;
+
+        for param in params.filter(|p| !p.is_empty()) {
+            let mut chars = param.chars();
+            let tag = chars.next();
+            let value = chars.as_str();
+            match tag {
+                Some('W') => width = Some(parse_num(value, "width")?),
+                Some('H') => height = Some(parse_num(value, "height")?),
+                Some('F') => {
+                    let (n, d) = value
+                        .split_once(':')
+                        .ok_or_else(|| invalid_data("invalid frame rate"))?;
+                    frame_rate = (parse_num(n, "frame rate")?, parse_num(d, "frame rate")?);
+                }
+                Some('C') => {
+                    chroma = match value {
+                        "420jpeg" | "420paldv" | "420mpeg2" | "420" => Chroma::C420,
+                        "422" => Chroma::C422,
+                        "444" => Chroma::C444,
+                        "mono" => Chroma::Mono,
+                        other => {
+                            return Err(io::Error::new(
+                                ErrorKind::Unsupported,
+                                format!("unsupported YUV4MPEG2 colorspace `{other}`"),
+                            ))
+                        }
+                    }
+                }
+                Some('X') => {
+                    if let Some(range) = value.strip_prefix("COLORRANGE=") {
+                        full_range = range.eq_ignore_ascii_case("FULL");
+                    }
+                }
+                // The interlacing, aspect ratio and the other tags doesn't affect the decoding.
+                _ => {}
+            }
+        }
+
+        let (Some(width), Some(height)) = (width, height) else {
+            return Err(invalid_data(
+                "the YUV4MPEG2 header is missing the frame size",
+            ));
+        };
+        if width == 0 || height == 0 {
+            return Err(invalid_data("the YUV4MPEG2 frames can't be empty"));
+        }
+        if frame_rate.0 == 0 || frame_rate.1 == 0 {
+            return Err(invalid_data("invalid frame rate"));
+        }
+
+        Ok(Self {
+            reader,
+            width,
+            height,
+            frame_rate,
+            chroma,
+            full_range,
+            buf: Vec::new(),
+        })
+    }
+
+    /// The frames dimensions.
+    #[inline(always)]
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// The stream frame rate, as numerator and denominator.
+    #[inline(always)]
+    pub fn frame_rate(&self) -> (u32, u32) {
+        self.frame_rate
+    }
+
+    /// How long each frame should stay on the screen.
+    #[inline]
+    pub fn frame_duration(&self) -> Duration {
+        Duration::from_secs(self.frame_rate.1 as u64) / self.frame_rate.0
+    }
+
+    /// The stream chroma subsampling.
+    #[inline(always)]
+    pub fn chroma(&self) -> Chroma {
+        self.chroma
+    }
+
+    /// Read the next frame, return [`None`] at the end of the stream.
+    pub fn read_frame(&mut self) -> io::Result<Option<VideoFrame>> {
+        let Some(header) = read_line(&mut self.reader)? else {
+            return Ok(None);
+        };
+        if header.split(' ').next() != Some(Y4M_FRAME) {
+            return Err(invalid_data("invalid YUV4MPEG2 frame header"));
+        }
+
+        let (w, h) = (self.width as usize, self.height as usize);
+        let (cw, ch) = self.chroma.plane_dimensions(w, h);
+        self.buf.resize(w * h + 2 * cw * ch, 0);
+        self.reader.read_exact(&mut self.buf)?;
+
+        let (luma, chroma) = self.buf.split_at(w * h);
+        let (u, v) = chroma.split_at(cw * ch);
+        let mut data = Vec::with_capacity(w * h * 3);
+        for y in 0..h {
+            for x in 0..w {
+                let (cb, cr) = if self.chroma == Chroma::Mono {
+                    (128, 128)
+                } else {
+                    let i = (y * ch / h) * cw + x * cw / w;
+                    (u[i], v[i])
+                };
+                data.extend_from_slice(&yuv_to_rgb(luma[y * w + x], cb, cr, self.full_range));
+            }
+        }
+
+        Ok(Some(VideoFrame {
+            width: self.width,
+            height: self.height,
+            data,
+        }))
+    }
+}
+
+impl<R: BufRead> Iterator for Y4mReader<R> {
+    type Item = io::Result<VideoFrame>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_frame().transpose()
+    }
+}
+
+/// A reader for raw `rgb24` streams (e.g. `ffmpeg -f rawvideo -pix_fmt rgb24`), with a known frame size.
+///
+/// ```
+/// # use aarty::{PixelImage, Rgba, video::RawRgbReader};
+/// let stream = [255, 0, 0, 0, 0, 255];
+/// let mut frames = RawRgbReader::new(stream.as_slice(), 1, 1).unwrap();
+///
+/// assert_eq!(frames.next().unwrap().unwrap().get_pixel(0, 0), Rgba { r: 255, g: 0, b: 0, a: 255 });
+/// assert_eq!(frames.next().unwrap().unwrap().get_pixel(0, 0), Rgba { r: 0, g: 0, b: 255, a: 255 });
+/// assert!(frames.next().is_none());
+///
+/// assert!(RawRgbReader::new(stream.as_slice(), 0, 0).is_err());
+/// ```
+#[derive(Debug)]
+pub struct RawRgbReader<R> {
+    reader: R,
+    width: u32,
+    height: u32,
+}
+
+impl<R: Read> RawRgbReader<R> {
+    /// Construct a new instance, return an error if the frame size is empty.
+    #[inline]
+    pub fn new(reader: R, width: u32, height: u32) -> io::Result<Self> {
+        if width == 0 || height == 0 {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "the raw frames can't be empty",
+            ));
+        }
+        Ok(Self {
+            reader,
+            width,
+            height,
+        })
+    }
+
+    /// The frames dimensions.
+    #[inline(always)]
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Read the next frame, return [`None`] at the end of the stream.
+    pub fn read_frame(&mut self) -> io::Result<Option<VideoFrame>> {
+        let mut data = vec![0; self.width as usize * self.height as usize * 3];
+        let mut read = 0;
+        while read < data.len() {
+            match self.reader.read(&mut data[read..]) {
+                Ok(0) if read == 0 => return Ok(None),
+                Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+                Ok(n) => read += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(Some(VideoFrame {
+            width: self.width,
+            height: self.height,
+            data,
+        }))
+    }
+}
+
+impl<R: Read> Iterator for RawRgbReader<R> {
+    type Item = io::Result<VideoFrame>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_frame().transpose()
+    }
+}
+
+/// Convert a BT.601 YCbCr value to RGB.
+#[inline]
+fn yuv_to_rgb(y: u8, cb: u8, cr: u8, full_range: bool) -> [u8; 3] {
+    let (y, cb, cr) = (y as f32, cb as f32 - 128., cr as f32 - 128.);
+    let (y, cb, cr) = if full_range {
+        (y, cb, cr)
+    } else {
+        ((y - 16.) * 255. / 219., cb * 255. / 224., cr * 255. / 224.)
+    };
+    [
+        (y + 1.402 * cr).round().clamp(0., 255.) as u8,
+        (y - 0.344_136 * cb - 0.714_136 * cr)
+            .round()
+            .clamp(0., 255.) as u8,
+        (y + 1.772 * cb).round().clamp(0., 255.) as u8,
+    ]
+}
+
+/// Read a `\n` terminated line (up to [`MAX_HEADER_LEN`] bytes), return [`None`] if the reader is already at its end.
+fn read_line<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut line = Vec::new();
+    let limit = MAX_HEADER_LEN as u64 + 1;
+    if reader.take(limit).read_until(b'\n', &mut line)? == 0 {
+        return Ok(None);
+    }
+    if line.pop() != Some(b'\n') {
+        if line.len() >= MAX_HEADER_LEN {
+            return Err(invalid_data("the YUV4MPEG2 header is too long"));
+        }
+        return Err(ErrorKind::UnexpectedEof.into());
+    }
+    String::from_utf8(line)
+        .map(Some)
+        .map_err(|_| invalid_data("the YUV4MPEG2 headers should be UTF-8"))
+}
+
+#[inline]
+fn parse_num(value: &str, name: &str) -> io::Result<u32> {
+    value
+        .parse()
+        .map_err(|e| invalid_data(&format!("invalid {name} `{value}`: {e}")))
+}
+
+#[inline]
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, msg.to_string())
+}
@@ -0,0 +1,202 @@
+pub use crate::resample::Sampler;
+use crate::{resample, PixelImage, Rgba};
+
+/// A rectangle of an image.
+///
+/// ```
+/// # use aarty::{buffer::FnPixelImage, view::Crop, PixelImage, Rgba};
+/// let image = FnPixelImage::new(8, 8, |x, y| Rgba { r: x as u8, g: y as u8, b: 0, a: 255 });
+/// let crop = Crop::new(&image, 2, 3, 4, 4);
+/// assert_eq!(crop.dimensions(), (4, 4));
+/// assert_eq!(crop.get_pixel(0, 0), Rgba { r: 2, g: 3, b: 0, a: 255 });
+///
+/// // The rectangle is clamped to the image.
+/// assert_eq!(Crop::new(&image, 6, 6, 4, 4).dimensions(), (2, 2));
+/// ```
+#[derive(Debug)]
+pub struct Crop<'a, I: ?Sized> {
+    image: &'a I,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+impl<'a, I: PixelImage + ?Sized> Crop<'a, I> {
+    /// Construct a new view of the `width` by `height` rectangle that starts at `x` and `y`.
+    ///
+    /// The rectangle is clamped to the image bounds, so it may be smaller (or even empty).
+    pub fn new(image: &'a I, x: u32, y: u32, width: u32, height: u32) -> Self {
+        let (iw, ih) = image.dimensions();
+        let (x, y) = (x.min(iw), y.min(ih));
+        Self {
+            image,
+            x,
+            y,
+            width: width.min(iw - x),
+            height: height.min(ih - y),
+        }
+    }
+
+    /// The top left corner of the rectangle, in the image.
+    #[inline]
+    pub fn offset(&self) -> (u32, u32) {
+        (self.x, self.y)
+    }
+}
+
+impl<I: PixelImage + ?Sized> PixelImage for Crop<'_, I> {
+    #[inline(always)]
+    fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    #[inline(always)]
+    fn get_pixel(&self, x: u32, y: u32) -> Rgba {
+        self.image.get_pixel(self.x + x, self.y + y)
+    }
+}
+
+/// Mirror an image horizontally (the left becomes the right).
+///
+/// ```
+/// # use aarty::{buffer::FnPixelImage, view::FlipH, PixelImage, Rgba};
+/// let image = FnPixelImage::new(3, 1, |x, _| Rgba { r: x as u8, g: 0, b: 0, a: 255 });
+/// assert_eq!(FlipH(&image).get_pixel(0, 0).r, 2);
+/// ```
+#[derive(Debug)]
+pub struct FlipH<'a, I: ?Sized>(pub &'a I);
+
+impl<I: PixelImage + ?Sized> PixelImage for FlipH<'_, I> {
+    #[inline(always)]
+    fn dimensions(&self) -> (u32, u32) {
+        self.0.dimensions()
+    }
+
+    #[inline]
+    fn get_pixel(&self, x: u32, y: u32) -> Rgba {
+        let (width, _) = self.0.dimensions();
+        self.0.get_pixel(width - 1 - x, y)
+    }
+}
+
+/// Mirror an image vertically (the top becomes the bottom).
+///
+/// ```
+/// # use aarty::{buffer::FnPixelImage, view::FlipV, PixelImage, Rgba};
+/// let image = FnPixelImage::new(1, 3, |_, y| Rgba { r: y as u8, g: 0, b: 0, a: 255 });
+/// assert_eq!(FlipV(&image).get_pixel(0, 0).r, 2);
+/// ```
+#[derive(Debug)]
+pub struct FlipV<'a, I: ?Sized>(pub &'a I);
+
+impl<I: PixelImage + ?Sized> PixelImage for FlipV<'_, I> {
+    #[inline(always)]
+    fn dimensions(&self) -> (u32, u32) {
+        self.0.dimensions()
+    }
+
+    #[inline]
+    fn get_pixel(&self, x: u32, y: u32) -> Rgba {
+        let (_, height) = self.0.dimensions();
+        self.0.get_pixel(x, height - 1 - y)
+    }
+}
+
+/// Rotate an image by 90 degrees clockwise, the other angles are made by nesting the views.
+///
+/// ```
+/// # use aarty::{buffer::FnPixelImage, view::Rotate90, PixelImage, Rgba};
+/// // The bottom left pixel is red.
+/// let image = FnPixelImage::new(3, 2, |x, y| {
+///     let r = if (x, y) == (0, 1) { 255 } else { 0 };
+///     Rgba { r, g: 0, b: 0, a: 255 }
+/// });
+/// let rotated = Rotate90(&image);
+/// assert_eq!(rotated.dimensions(), (2, 3));
+/// // Now it's the top left one.
+/// assert_eq!(rotated.get_pixel(0, 0).r, 255);
+///
+/// // 180 degrees.
+/// let rotated = Rotate90(&rotated);
+/// assert_eq!(rotated.dimensions(), (3, 2));
+/// assert_eq!(rotated.get_pixel(2, 0).r, 255);
+/// ```
+#[derive(Debug)]
+pub struct Rotate90<'a, I: ?Sized>(pub &'a I);
+
+impl<I: PixelImage + ?Sized> PixelImage for Rotate90<'_, I> {
+    #[inline]
+    fn dimensions(&self) -> (u32, u32) {
+        let (width, height) = self.0.dimensions();
+        (height, width)
+    }
+
+    #[inline]
+    fn get_pixel(&self, x: u32, y: u32) -> Rgba {
+        let (_, height) = self.0.dimensions();
+        self.0.get_pixel(y, height - 1 - x)
+    }
+}
+
+/// Resize an image on the fly, every pixel is sampled when it's read.
+///
+/// It's cheaper than [`resample::resize`] when only a few pixels are read (e.g. a cropped part),
+/// but [`resample::resize`] is faster for the whole image with the filters that mix many pixels,
+/// since it mixes the rows and the columns separately.
+///
+/// ```
+/// # use aarty::{buffer::FnPixelImage, view::{Sampler, Scaled}, PixelImage, Rgba};
+/// // Black and white columns.
+/// let image = FnPixelImage::new(4, 4, |x, _| {
+///     let v = if x % 2 == 0 { 0 } else { 254 };
+///     Rgba { r: v, g: v, b: v, a: 255 }
+/// });
+///
+/// let nearest = Scaled::new(&image, (2, 2), Sampler::Nearest);
+/// assert_eq!(nearest.dimensions(), (2, 2));
+/// assert_eq!(nearest.get_pixel(0, 0).r, 254);
+///
+/// let averaged = Scaled::new(&image, (2, 2), Sampler::Box);
+/// assert_eq!(averaged.get_pixel(0, 0).r, 127);
+///
+/// // An empty image is scaled to transparent pixels.
+/// let empty = FnPixelImage::new(0, 0, |_, _| unreachable!());
+/// assert_eq!(Scaled::new(&empty, (2, 2), Sampler::Lanczos3).get_pixel(1, 1).a, 0);
+/// ```
+#[derive(Debug)]
+pub struct Scaled<'a, I: ?Sized> {
+    image: &'a I,
+    size: (u32, u32),
+    sampler: Sampler,
+}
+
+impl<'a, I: PixelImage + ?Sized> Scaled<'a, I> {
+    /// Construct a new view with the `size` (width and height).
+    #[inline]
+    pub fn new(image: &'a I, size: (u32, u32), sampler: Sampler) -> Self {
+        Self {
+            image,
+            size,
+            sampler,
+        }
+    }
+
+    /// The sampler.
+    #[inline(always)]
+    pub fn sampler(&self) -> Sampler {
+        self.sampler
+    }
+}
+
+impl<I: PixelImage + ?Sized> PixelImage for Scaled<'_, I> {
+    #[inline(always)]
+    fn dimensions(&self) -> (u32, u32) {
+        self.size
+    }
+
+    #[inline]
+    fn get_pixel(&self, x: u32, y: u32) -> Rgba {
+        resample::sample(self.image, x, y, self.size, self.sampler)
+    }
+}
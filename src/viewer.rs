@@ -0,0 +1,561 @@
+use std::{
+    collections::VecDeque,
+    error::Error,
+    io::{self, Read, Write},
+    time::Duration,
+};
+
+use crate::{
+    convert_image_to_ascii,
+    style::{Ansi, Command, StyleBackend, StyleWriter},
+    sympols::PRESETS,
+    terminal::{Fit, CELL_ASPECT},
+    view::{Crop, Sampler, Scaled},
+    Config, PixelImage, Sympols, COLORS, REVERSE,
+};
+
+/// The zoom factor of a single [`Action::ZoomIn`] or [`Action::ZoomOut`].
+const ZOOM_STEP: f32 = 1.25;
+const MIN_ZOOM: f32 = 0.1;
+const MAX_ZOOM: f32 = 32.;
+/// How long to wait for the rest of an escape sequence before reading it as the escape key.
+const ESCAPE_TIMEOUT: Duration = Duration::from_millis(50);
+/// The longest escape sequence that is waited for, a longer one without the final byte is garbage.
+const MAX_ESCAPE_LEN: usize = 16;
+
+/// A key press.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[non_exhaustive]
+pub enum Key {
+    /// A printable character.
+    Char(char),
+    /// A character with the control key (e.g. `Ctrl('c')`).
+    Ctrl(char),
+    /// Arrow up.
+    Up,
+    /// Arrow down.
+    Down,
+    /// Arrow left.
+    Left,
+    /// Arrow right.
+    Right,
+    /// Page up.
+    PageUp,
+    /// Page down.
+    PageDown,
+    /// Home.
+    Home,
+    /// End.
+    End,
+    /// Enter.
+    Enter,
+    /// Tab.
+    Tab,
+    /// Backspace.
+    Backspace,
+    /// Escape.
+    Esc,
+}
+
+/// Decode the key presses from the terminal input (in the raw mode).
+///
+/// An escape sequence that is split between two reads is kept until the rest arrives,
+/// so the escape key alone is only known when the next read (or the end) comes, See: [`Keys::with_pending`].
+///
+/// ```
+/// # use aarty::viewer::{Key, Keys};
+/// # use std::io;
+/// let keys: Vec<Key> = Keys::new(&b"jk\x1b[A\x1b[6~q\x03"[..]).map(Result::unwrap).collect();
+/// assert_eq!(
+///     keys,
+///     [Key::Char('j'), Key::Char('k'), Key::Up, Key::PageDown, Key::Char('q'), Key::Ctrl('c')]
+/// );
+///
+/// // The arrow is split between two reads, and the escape key is the last one.
+/// let reads = io::Read::chain(&b"\x1b"[..], &b"[Bj\x1b"[..]);
+/// let keys: Vec<Key> = Keys::new(reads).map(Result::unwrap).collect();
+/// assert_eq!(keys, [Key::Down, Key::Char('j'), Key::Esc]);
+/// ```
+#[derive(Debug)]
+pub struct Keys<R> {
+    reader: R,
+    keys: VecDeque<Key>,
+    /// An incomplete UTF-8 character or escape sequence from the previous read.
+    rest: Vec<u8>,
+    pending: Option<fn(Duration) -> io::Result<bool>>,
+}
+
+impl<R: Read> Keys<R> {
+    /// Construct a new instance.
+    #[inline]
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            keys: VecDeque::new(),
+            rest: Vec::new(),
+            pending: None,
+        }
+    }
+
+    /// Set a function that waits (up to the timeout) for more input, and returns false if nothing came.
+    ///
+    /// It's called when the input ends in the middle of an escape sequence, so a lone escape key press
+    /// is read right away instead of waiting for the next key, See: [`crate::terminal::input_pending`].
+    #[inline]
+    #[must_use]
+    pub fn with_pending(mut self, pending: fn(Duration) -> io::Result<bool>) -> Self {
+        self.pending = Some(pending);
+        self
+    }
+
+    /// Parse the rest as it is, even if it ends with an incomplete sequence.
+    fn flush(&mut self) {
+        parse_keys(&self.rest, &mut self.keys, true);
+        self.rest.clear();
+    }
+}
+
+impl<R: Read> Iterator for Keys<R> {
+    type Item = io::Result<Key>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut buf = [0; 256];
+        while self.keys.is_empty() {
+            if let (Some(0x1b), Some(pending)) = (self.rest.first(), self.pending) {
+                match pending(ESCAPE_TIMEOUT) {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        self.flush();
+                        continue;
+                    }
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+            let n = match self.reader.read(&mut buf) {
+                Ok(0) if self.rest.is_empty() => return None,
+                Ok(0) => {
+                    self.flush();
+                    continue;
+                }
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Some(Err(e)),
+            };
+            self.rest.extend_from_slice(&buf[..n]);
+            let consumed = parse_keys(&self.rest, &mut self.keys, false);
+            self.rest.drain(..consumed);
+        }
+        self.keys.pop_front().map(Ok)
+    }
+}
+
+/// Parse the keys in `bytes`, and return how many bytes are consumed.
+///
+/// An incomplete UTF-8 character or escape sequence at the end is kept, unless the input is `complete`.
+fn parse_keys(bytes: &[u8], keys: &mut VecDeque<Key>, complete: bool) -> usize {
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let key = match b {
+            0x1b => {
+                let Some((key, len)) = parse_escape(&bytes[i..], complete) else {
+                    return i;
+                };
+                i += len;
+                if let Some(key) = key {
+                    keys.push_back(key);
+                }
+                continue;
+            }
+            b'\r' | b'\n' => Key::Enter,
+            b'\t' => Key::Tab,
+            0x7f | 0x08 => Key::Backspace,
+            0x01..=0x1a => Key::Ctrl((b'a' + b - 1) as char),
+            _ if b.is_ascii() => Key::Char(b as char),
+            _ => {
+                let len = match b {
+                    0xc0..=0xdf => 2,
+                    0xe0..=0xef => 3,
+                    0xf0..=0xf7 => 4,
+                    // A stray continuation byte.
+                    _ => {
+                        i += 1;
+                        continue;
+                    }
+                };
+                let Some(ch) = bytes.get(i..i + len) else {
+                    return i;
+                };
+                i += len;
+                if let Some(c) = std::str::from_utf8(ch).ok().and_then(|s| s.chars().next()) {
+                    keys.push_back(Key::Char(c));
+                }
+                continue;
+            }
+        };
+        keys.push_back(key);
+        i += 1;
+    }
+    i
+}
+
+/// Parse an escape sequence, return the key (if it's known) and the sequence length,
+/// or [`None`] if the sequence isn't `complete` yet.
+fn parse_escape(bytes: &[u8], complete: bool) -> Option<(Option<Key>, usize)> {
+    match bytes.get(1) {
+        Some(b'[' | b'O') => {}
+        None if !complete => return None,
+        _ => return Some((Some(Key::Esc), 1)),
+    }
+    // CSI (or SS3) parameters, then the final byte.
+    let Some(end) = bytes[2..].iter().position(|b| (0x40..=0x7e).contains(b)) else {
+        return (complete || bytes.len() > MAX_ESCAPE_LEN).then_some((Some(Key::Esc), 1));
+    };
+    let params = &bytes[2..2 + end];
+    let key = match bytes[2 + end] {
+        b'A' => Some(Key::Up),
+        b'B' => Some(Key::Down),
+        b'C' => Some(Key::Right),
+        b'D' => Some(Key::Left),
+        b'H' => Some(Key::Home),
+        b'F' => Some(Key::End),
+        b'~' => match params {
+            b"1" | b"7" => Some(Key::Home),
+            b"4" | b"8" => Some(Key::End),
+            b"5" => Some(Key::PageUp),
+            b"6" => Some(Key::PageDown),
+            _ => None,
+        },
+        _ => None,
+    };
+    Some((key, end + 3))
+}
+
+/// What the viewer should do.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[non_exhaustive]
+pub enum Action {
+    /// Move the viewport, in steps of 1/8 of the screen.
+    Pan(i32, i32),
+    /// Zoom in.
+    ZoomIn,
+    /// Zoom out.
+    ZoomOut,
+    /// Fit the image to the screen.
+    Reset,
+    /// Show the next image.
+    Next,
+    /// Show the previous image.
+    Previous,
+    /// Toggle the [`COLORS`] flag.
+    ToggleColors,
+    /// Toggle the [`REVERSE`] flag.
+    ToggleReverse,
+    /// Use the next sympols preset, See: [`PRESETS`].
+    NextPreset,
+    /// Quit.
+    Quit,
+}
+
+impl Action {
+    /// The default key bindings.
+    ///
+    /// | Keys | Action |
+    /// |------|--------|
+    /// | arrows, `h` `j` `k` `l` | pan |
+    /// | page up, page down | pan a whole screen |
+    /// | `+` `=`, `-` `_` | zoom in, zoom out |
+    /// | `0`, home | fit the screen |
+    /// | `n` space, `p` backspace | next, previous image |
+    /// | `c`, `r`, `s` | toggle the colors, the reverse mode, switch the sympols preset |
+    /// | `q`, esc, ctrl+c | quit |
+    pub fn from_key(key: Key) -> Option<Action> {
+        Some(match key {
+            Key::Left | Key::Char('h') => Action::Pan(-1, 0),
+            Key::Right | Key::Char('l') => Action::Pan(1, 0),
+            Key::Up | Key::Char('k') => Action::Pan(0, -1),
+            Key::Down | Key::Char('j') => Action::Pan(0, 1),
+            Key::PageUp => Action::Pan(0, -8),
+            Key::PageDown => Action::Pan(0, 8),
+            Key::Char('+' | '=') => Action::ZoomIn,
+            Key::Char('-' | '_') => Action::ZoomOut,
+            Key::Char('0') | Key::Home => Action::Reset,
+            Key::Char('n' | ' ') => Action::Next,
+            Key::Char('p') | Key::Backspace => Action::Previous,
+            Key::Char('c') => Action::ToggleColors,
+            Key::Char('r') => Action::ToggleReverse,
+            Key::Char('s') => Action::NextPreset,
+            Key::Char('q') | Key::Esc | Key::Ctrl('c') => Action::Quit,
+            _ => return None,
+        })
+    }
+}
+
+/// An interactive full-screen images viewer.
+///
+/// The images are fitted to the screen, and re-rendered from the source image on every zoom.
+/// The screen is drawn with the [`Ansi`] backend by default, See: [`Viewer::with_backend`].
+///
+/// ```
+/// # use aarty::{viewer::{Keys, Viewer}, Config, PixelImage, Rgba, Sympols};
+/// struct Solid(u32, u32);
+///
+/// impl PixelImage for Solid {
+///     fn dimensions(&self) -> (u32, u32) {
+///         (self.0, self.1)
+///     }
+///
+///     fn get_pixel(&self, _: u32, _: u32) -> Rgba {
+///         Rgba { r: 255, g: 255, b: 255, a: 255 }
+///     }
+/// }
+///
+/// let mut viewer = Viewer::new(Config::new(Sympols::new(vec![' ', '#'])));
+/// viewer.push("a.png", Solid(160, 160));
+/// viewer.push("b.png", Solid(320, 80));
+///
+/// // Zoom in twice, pan to the right, then switch to the next image and zoom in.
+/// let mut screen = Vec::new();
+/// viewer.run(Keys::new(&b"++ln+"[..]), &mut screen, || (80, 25)).unwrap();
+/// assert_eq!(viewer.index(), 1);
+/// assert_eq!(viewer.zoom(), 1.25);
+///
+/// // Back to the first image, it's reset.
+/// viewer.run(Keys::new(&b"p\x1b[C"[..]), &mut screen, || (80, 25)).unwrap();
+/// assert_eq!((viewer.index(), viewer.zoom(), viewer.offset()), (0, 1., (0, 0)));
+/// ```
+#[derive(Debug)]
+pub struct Viewer<I, B = Ansi> {
+    images: Vec<(String, I)>,
+    config: Config,
+    backend: B,
+    index: usize,
+    zoom: f32,
+    /// The viewport top left corner in the zoomed image, in cells.
+    offset: (u32, u32),
+    /// The current preset index in [`PRESETS`], [`None`] if the sympols aren't a preset.
+    preset: Option<usize>,
+}
+
+impl<I: PixelImage> Viewer<I> {
+    /// Construct a new viewer without images.
+    #[inline]
+    pub fn new(config: Config) -> Self {
+        let preset = PRESETS
+            .iter()
+            .position(|(_, set)| Sympols::from(*set) == config.sympols);
+        Self {
+            images: Vec::new(),
+            config,
+            backend: Ansi,
+            index: 0,
+            zoom: 1.,
+            offset: (0, 0),
+            preset,
+        }
+    }
+}
+
+impl<I: PixelImage, B: StyleBackend> Viewer<I, B> {
+    /// Draw the screen with another backend (e.g. `style::Crossterm` with the `crossterm` feature).
+    #[inline]
+    pub fn with_backend<T: StyleBackend>(self, backend: T) -> Viewer<I, T> {
+        Viewer {
+            images: self.images,
+            config: self.config,
+            backend,
+            index: self.index,
+            zoom: self.zoom,
+            offset: self.offset,
+            preset: self.preset,
+        }
+    }
+
+    /// Add an image.
+    #[inline]
+    pub fn push(&mut self, name: impl Into<String>, image: I) {
+        self.images.push((name.into(), image));
+    }
+
+    /// The current image index.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The current zoom, `1` means that the image fits the screen.
+    #[inline]
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// The viewport top left corner in the zoomed image, in cells.
+    #[inline]
+    pub fn offset(&self) -> (u32, u32) {
+        self.offset
+    }
+
+    /// The current config.
+    #[inline]
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Apply an action, `area` is the screen area that the image is displayed in (in cells).
+    ///
+    /// Return false if the viewer should quit.
+    pub fn apply(&mut self, action: Action, area: (u32, u32)) -> bool {
+        match action {
+            Action::Pan(dx, dy) => {
+                let step = |v: u32| (v / 8).max(1) as i64;
+                self.offset = (
+                    (self.offset.0 as i64 + dx as i64 * step(area.0)).max(0) as u32,
+                    (self.offset.1 as i64 + dy as i64 * step(area.1)).max(0) as u32,
+                );
+            }
+            Action::ZoomIn | Action::ZoomOut => {
+                let zoom = if action == Action::ZoomIn {
+                    self.zoom * ZOOM_STEP
+                } else {
+                    self.zoom / ZOOM_STEP
+                }
+                .clamp(MIN_ZOOM, MAX_ZOOM);
+                // Keep the viewport center in place.
+                let (w, h) = self.rendered_size(area);
+                let center = |offset: u32, size: u32, area: u32| {
+                    let center = offset as f32 + size.min(area) as f32 / 2.;
+                    (center * zoom / self.zoom - area as f32 / 2.).max(0.) as u32
+                };
+                self.offset = (
+                    center(self.offset.0, w, area.0),
+                    center(self.offset.1, h, area.1),
+                );
+                self.zoom = zoom;
+            }
+            Action::Reset => self.reset(),
+            Action::Next | Action::Previous if !self.images.is_empty() => {
+                let len = self.images.len();
+                self.index = if action == Action::Next {
+                    (self.index + 1) % len
+                } else {
+                    (self.index + len - 1) % len
+                };
+                self.reset();
+            }
+            Action::Next | Action::Previous => {}
+            Action::ToggleColors => self.config.flags ^= COLORS,
+            Action::ToggleReverse => self.config.flags ^= REVERSE,
+            Action::NextPreset => {
+                let preset = self.preset.map_or(0, |p| (p + 1) % PRESETS.len());
+                self.config.sympols = Sympols::from(PRESETS[preset].1);
+                self.preset = Some(preset);
+            }
+            Action::Quit => return false,
+        }
+        self.clamp_offset(area);
+        true
+    }
+
+    /// Draw the current image and the status line, `size` is the terminal size (in cells).
+    pub fn render<W: Write>(&self, out: &mut W, size: (u32, u32)) -> Result<(), Box<dyn Error>> {
+        let area = (size.0, size.1.saturating_sub(1));
+        let mut frame = Vec::new();
+        let mut queue = |command| self.backend.queue(&mut frame, command);
+        queue(Command::ClearScreen)?;
+
+        if let Some((name, image)) = self.images.get(self.index) {
+            // Every cell averages the source pixels that it covers.
+            let scaled = Scaled::new(image, self.rendered_size(area), Sampler::Box);
+            let view = Crop::new(&scaled, self.offset.0, self.offset.1, area.0, area.1);
+            let (w, h) = view.dimensions();
+            let left = (area.0 - w) / 2;
+            let top = (area.1 - h) / 2;
+
+            let mut buf = StyleWriter::new(Vec::new(), &self.backend);
+            convert_image_to_ascii(&self.config, &view, &mut buf)?;
+            let buf = String::from_utf8(buf.into_inner())?;
+            for (i, line) in buf.split('\n').take(h as usize).enumerate() {
+                queue(Command::MoveTo(left as u16, (top + i as u32) as u16))?;
+                queue(Command::Print(line))?;
+            }
+            // Reset the colors if the last line ended with the background close code.
+            queue(Command::Reset)?;
+
+            let preset = self.preset.map_or("custom", |p| PRESETS[p].0);
+            let status = format!(
+                " {name} [{}/{}]  zoom {:.0}%  sympols: {preset}  colors: {}  reverse: {}",
+                self.index + 1,
+                self.images.len(),
+                self.zoom * 100.,
+                on_off(self.config.use_colors()),
+                on_off(self.config.reversed()),
+            );
+            let status: String = status.chars().take(size.0 as usize).collect();
+            let status = format!("{status:<w$}", w = size.0 as usize);
+            queue(Command::MoveTo(0, size.1.saturating_sub(1) as u16))?;
+            queue(Command::SetReverse)?;
+            queue(Command::Print(&status))?;
+            queue(Command::Reset)?;
+        }
+
+        out.write_all(&frame)?;
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Render, then handle the keys until [`Action::Quit`] or the keys end.
+    ///
+    /// `size` returns the terminal size, it's called before every render so the viewer follows the terminal resizes.
+    pub fn run<K, W, S>(&mut self, keys: K, out: &mut W, mut size: S) -> Result<(), Box<dyn Error>>
+    where
+        K: IntoIterator<Item = io::Result<Key>>,
+        W: Write,
+        S: FnMut() -> (u32, u32),
+    {
+        self.render(out, size())?;
+        for key in keys {
+            let Some(action) = Action::from_key(key?) else {
+                continue;
+            };
+            let size = size();
+            if !self.apply(action, (size.0, size.1.saturating_sub(1))) {
+                break;
+            }
+            self.render(out, size)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.zoom = 1.;
+        self.offset = (0, 0);
+    }
+
+    /// The size of the whole image at the current zoom, in cells.
+    fn rendered_size(&self, area: (u32, u32)) -> (u32, u32) {
+        let Some((_, image)) = self.images.get(self.index) else {
+            return (0, 0);
+        };
+        let (w, h) = Fit::Contain.fit(image.dimensions(), area, CELL_ASPECT);
+        let zoom = |v: u32| ((v as f32 * self.zoom).round() as u32).max(1);
+        (zoom(w), zoom(h))
+    }
+
+    fn clamp_offset(&mut self, area: (u32, u32)) {
+        let (w, h) = self.rendered_size(area);
+        self.offset = (
+            self.offset.0.min(w.saturating_sub(area.0)),
+            self.offset.1.min(h.saturating_sub(area.1)),
+        );
+    }
+}
+
+#[inline]
+fn on_off(v: bool) -> &'static str {
+    if v {
+        "on"
+    } else {
+        "off"
+    }
+}
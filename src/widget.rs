@@ -0,0 +1,196 @@
+use ratatui::{buffer::Buffer, layout::Rect, style::Color, widgets::Widget};
+
+use crate::{
+    color::ANSIColor,
+    terminal::{Fit, CELL_ASPECT},
+    view::{Crop, Sampler, Scaled},
+    Config, PixelImage, Rgba, TextImage, ToTextImage,
+};
+
+/// The upper half block, the foreground draws the upper pixel and the background draws the lower one.
+const UPPER_HALF_BLOCK: char = '▀';
+
+/// A [`Widget`] that draws a [`PixelImage`] resized to the area.
+///
+/// ```
+/// # use aarty::{widget::Image, Config, PixelImage, Rgba, Sympols, COLORS};
+/// # use ratatui::{buffer::Buffer, layout::Rect, style::Color, widgets::Widget};
+/// struct Gradient;
+///
+/// impl PixelImage for Gradient {
+///     fn dimensions(&self) -> (u32, u32) {
+///         (8, 4)
+///     }
+///
+///     fn get_pixel(&self, x: u32, _: u32) -> Rgba {
+///         let v = if x < 4 { 0 } else { 255 };
+///         Rgba { r: v, g: v, b: v, a: 255 }
+///     }
+/// }
+///
+/// let config = Config::new(Sympols::new(vec![' ', '#']));
+/// let mut buf = Buffer::empty(Rect::new(0, 0, 8, 2));
+/// Image::new(&Gradient, &config).render(buf.area, &mut buf);
+/// assert_eq!(buf, Buffer::with_lines(vec!["    ####", "    ####"]));
+///
+/// // Two pixels per cell, with the colors.
+/// let config = config.with_flags(COLORS);
+/// let mut buf = Buffer::empty(Rect::new(0, 0, 8, 2));
+/// Image::new(&Gradient, &config).half_blocks(true).render(buf.area, &mut buf);
+/// let cell = buf.get(7, 1);
+/// assert_eq!(cell.symbol, "▀");
+/// assert_eq!((cell.fg, cell.bg), (Color::Rgb(255, 255, 255), Color::Rgb(255, 255, 255)));
+/// ```
+#[derive(Debug)]
+pub struct Image<'a, I> {
+    image: &'a I,
+    config: &'a Config,
+    fit: Fit,
+    sampler: Sampler,
+    half_blocks: bool,
+}
+
+impl<'a, I: PixelImage> Image<'a, I> {
+    /// Construct a new widget, the image is fitted inside the area by default (See: [`Fit::Contain`]).
+    #[inline]
+    pub fn new(image: &'a I, config: &'a Config) -> Self {
+        Self {
+            image,
+            config,
+            fit: Fit::Contain,
+            sampler: Sampler::Nearest,
+            half_blocks: false,
+        }
+    }
+
+    /// How to fit the image into the area.
+    #[inline]
+    #[must_use]
+    pub fn fit(mut self, fit: Fit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    /// How the image is resized to the area, the nearest pixel by default.
+    #[inline]
+    #[must_use]
+    pub fn sampler(mut self, sampler: Sampler) -> Self {
+        self.sampler = sampler;
+        self
+    }
+
+    /// Draw two pixels per cell with the upper half block character,
+    /// the sympols are ignored in this mode, and it's only used if the config has the [`crate::COLORS`] flag.
+    #[inline]
+    #[must_use]
+    pub fn half_blocks(mut self, enabled: bool) -> Self {
+        self.half_blocks = enabled;
+        self
+    }
+}
+
+impl<I: PixelImage> Widget for Image<'_, I> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (iw, ih) = self.image.dimensions();
+        if area.is_empty() || iw == 0 || ih == 0 {
+            return;
+        }
+        let half_blocks = self.half_blocks && self.config.use_colors();
+        // Every cell is two pixels tall in the half blocks mode.
+        let (rows, aspect) = if half_blocks {
+            (area.height as u32 * 2, CELL_ASPECT / 2.)
+        } else {
+            (area.height as u32, CELL_ASPECT)
+        };
+        let (w, h) = self.fit.fit((iw, ih), (area.width as u32, rows), aspect);
+        // Crop the center if the image overflows the area (e.g. with `Fit::Cover`).
+        let size = (w.min(area.width as u32), h.min(rows));
+        let scaled = Scaled::new(self.image, (w, h), self.sampler);
+        let resized = Crop::new(&scaled, (w - size.0) / 2, (h - size.1) / 2, size.0, size.1);
+
+        if !half_blocks {
+            let text = resized.to_text(self.config.clone());
+            let (w, h) = resized.dimensions();
+            return (&text).render(center(area, w as u16, h as u16), buf);
+        }
+
+        let (w, h) = resized.dimensions();
+        let area = center(area, w as u16, ((h + 1) / 2) as u16);
+        let background = self.config.background_color();
+        let color = |x: u32, y: u32, pixel: Option<Rgba>| match pixel
+            .map(|pixel| self.config.prepare(x, y, pixel))
+        {
+            Some(pixel) if pixel.a > 0 => {
+                to_color(&ANSIColor::from(pixel).with_depth(self.config.color_depth))
+            }
+            _ => background.as_ref().map_or(Color::Reset, to_color),
+        };
+        for y in 0..area.height {
+            for x in 0..area.width {
+                let upper = resized.get_pixel(x as u32, y as u32 * 2);
+                let lower =
+                    (y as u32 * 2 + 1 < h).then(|| resized.get_pixel(x as u32, y as u32 * 2 + 1));
+                buf.get_mut(area.x + x, area.y + y)
+                    .set_char(UPPER_HALF_BLOCK)
+                    .set_fg(color(x as u32, y as u32 * 2, Some(upper)))
+                    .set_bg(color(x as u32, y as u32 * 2 + 1, lower));
+            }
+        }
+    }
+}
+
+/// Draw the [`TextImage`] fragments as they are, from the area top left corner and clipped to it.
+impl Widget for &TextImage {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let colored = self.config.use_colors();
+        let background = self.config.background_color();
+        let rows = self.rows().min(area.height as usize);
+        let cols = self.row_len.min(area.width as usize);
+
+        for y in 0..rows {
+            for x in 0..cols {
+                let Some(fragment) = self.get(y * self.row_len + x) else {
+                    continue;
+                };
+                let cell = buf.get_mut(area.x + x as u16, area.y + y as u16);
+                cell.set_char(fragment.sym());
+                if !colored {
+                    continue;
+                }
+                let (bg, fg) = self
+                    .config
+                    .cell_style(fragment.foreground(), background.clone());
+                if let Some(fg) = fg {
+                    cell.set_fg(to_color(&fg));
+                }
+                if let Some(bg) = bg {
+                    cell.set_bg(to_color(&bg));
+                }
+            }
+        }
+    }
+}
+
+/// Convert the color into a [`ratatui`] color, the transparent color is the terminal default.
+fn to_color(color: &ANSIColor) -> Color {
+    if let Some((r, g, b)) = color.rgb() {
+        Color::Rgb(r, g, b)
+    } else if let Some(idx) = color.index() {
+        Color::Indexed(idx)
+    } else {
+        Color::Reset
+    }
+}
+
+/// A rect with the given size centered in `area`.
+#[inline]
+fn center(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}